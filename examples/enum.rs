@@ -3,6 +3,7 @@ use std::convert::TryFrom;
 use std::sync::{Arc, Mutex};
 
 #[derive(Wrap, Unwrap, Debug)]
+#[giftwrap(kind = MyEnumKind, reflect, eq)]
 pub enum MyEnum {
     #[giftwrap(noWrap = true)]
     UnwrappedNumber {
@@ -19,25 +20,119 @@ pub enum MyEnum {
 pub struct Str<'a>(&'a str);
 
 #[derive(Wrap, Unwrap, Debug)]
+#[giftwrap(kind = MyGenericEnumKind, kindDerive = [Debug, PartialEq])]
 pub enum MyGenericEnum<'a, T> {
     Str(Str<'a>),
     #[giftwrap(wrapDepth = 1)]
     Gen(Option<T>),
     #[giftwrap(wrapDepth = 0)]
     Dep(Arc<Mutex<i32>>),
-    #[giftwrap(noWrap = true, noUnwrap = true)]
+    #[giftwrap(noWrap = true, intoInner = true)]
     T(T),
 }
 
+#[derive(Wrap, Unwrap, Debug)]
+#[giftwrap(eq)]
+pub enum Event<T> {
+    #[giftwrap(wrapDepth = 1, bound = "T: PartialEq + std::fmt::Debug + 'static")]
+    Payload(Option<T>),
+    Code(u16),
+}
+
+#[derive(Wrap, Debug)]
+pub enum Tagged<T> {
+    #[giftwrap(wrapDepth = 1)]
+    Left((T, i32)),
+    #[giftwrap(wrapDepth = 1)]
+    Right((u8, T)),
+}
+
+#[derive(Wrap, Debug)]
+pub enum One<T> {
+    A(T),
+}
+
+#[derive(Wrap, Debug)]
+pub enum Expr {
+    Lit(i64),
+    #[giftwrap(wrapDepth = 0)]
+    Neg(Box<Expr>),
+}
+
+#[derive(Wrap, Debug)]
+pub enum AnyError {
+    #[giftwrap(noWrap)]
+    Message(String),
+    #[giftwrap(anyImpl)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Named like the type parameter of the `anyImpl` impl, which must not shadow it
+#[derive(Wrap)]
+pub enum E {
+    #[giftwrap(anyImpl)]
+    Any(Box<dyn std::fmt::Display>),
+}
+
+pub type Label = String;
+
+pub type Tally = std::collections::HashMap<String, u32>;
+
+#[derive(Wrap, Unwrap, Debug)]
+#[giftwrap(eq)]
+pub enum Cell {
+    #[giftwrap(noWrap)]
+    Text(String),
+    #[giftwrap(noWrap, sameAs = String)]
+    Label(Label),
+    Count(std::primitive::u32),
+    #[giftwrap(noWrap)]
+    Counts(std::collections::HashMap<String, u32>),
+    #[giftwrap(noWrap, sameAs = std::collections::HashMap<String, u32>)]
+    Tally(Tally),
+}
+
+#[derive(Wrap, Unwrap, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum Opcode {
+    Nop,
+    Push = 4,
+    Pop,
+    Jump = 10,
+}
+
+#[derive(Wrap, Unwrap, Debug, Clone, Copy)]
+#[giftwrap(strings, rename_all = "kebab-case")]
+pub enum LogLevel {
+    Error,
+    #[giftwrap(alias = ["warn"])]
+    Warning,
+    #[giftwrap(name = "information")]
+    Info,
+    DebugTrace,
+}
+
 fn main() {
     println!("{:?}", MyEnum::from(12));
     println!("{:?}", i64::try_from(MyEnum::UnwrappedNumber { n: 12 }));
 
     println!("{:?}", MyEnum::from(String::from("<-")));
     println!("{:?}", String::try_from(MyEnum::Text(String::from("->"))));
+    println!("{:?}", MyEnum::WrappedNumber { n: 1 }.kind());
+    println!("{:?}", MyEnumKind::from(&MyEnum::from(String::new())));
+    println!("{:?}", MyEnum::VARIANTS);
+    println!(
+        "{} {}",
+        MyEnum::UnwrappedNumber { n: 3 } == 3,
+        MyEnum::Text(String::from("3")) == 3
+    );
+    println!("{}", MyEnum::Text(String::new()).variant_name());
 
     println!("{:?}", MyGenericEnum::<()>::from(Str("<=")));
-    println!("{:?}", Str::try_from(MyGenericEnum::<()>::Str(Str("=>"))));
+    println!(
+        "{:?}",
+        Str::try_from(MyGenericEnum::<()>::Str(Str("=>"))).map(|s| s.0)
+    );
 
     println!("{:?}", MyGenericEnum::<bool>::from(Some(true)));
     println!(
@@ -51,4 +146,40 @@ fn main() {
         "{:?}",
         MyGenericEnum::<()>::from(Arc::new(Mutex::new(3i32)))
     );
+
+    println!("{:?}", MyGenericEnum::T(4u8).try_into_t());
+    println!("{}", MyGenericEnum::T(5u8).kind() == MyGenericEnumKind::T);
+
+    println!("{:?}", Event::from(Some("payload")));
+    println!("{}", Event::<()>::Code(7) == 7);
+
+    println!("{:?}", Tagged::<bool>::from((true, 1)));
+    println!("{:?}", Tagged::<bool>::from((2, false)));
+    println!("{:?}", One::from('a'));
+
+    println!("{:?}", Expr::from(Box::new(Expr::from(5))));
+
+    println!("{:?}", AnyError::Message(String::from("any")));
+    println!("{:?}", AnyError::from(std::fmt::Error));
+    let E::Any(any) = E::from(7u8);
+    println!("{any}");
+
+    println!("{:?}", String::try_from(Cell::Label(Label::from("label"))));
+    println!("{:?}", u32::try_from(Cell::Text(String::new())));
+    println!("{}", Cell::Count(2) == 2u32);
+    println!(
+        "{:?}",
+        Tally::try_from(Cell::Tally(Tally::from([(String::from("a"), 1)])))
+    );
+
+    println!("{:?}", u8::from(Opcode::Pop));
+    println!("{:?}", Opcode::try_from(10));
+    println!("{:?}", Opcode::try_from(3));
+
+    println!("{:?}", "warn".parse::<LogLevel>());
+    println!("{:?}", LogLevel::try_from("debug-trace"));
+    println!("{}", <&str>::from(LogLevel::Info));
+    if let Err(e) = "trace".parse::<LogLevel>() {
+        println!("{e}");
+    }
 }
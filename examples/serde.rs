@@ -0,0 +1,51 @@
+use giftwrap::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Wrap, Debug, PartialEq)]
+#[giftwrap(serde)]
+pub struct Celsius(f64);
+
+#[derive(Wrap, Debug)]
+#[giftwrap(serde)]
+pub struct Named<T> {
+    #[giftwrap(wrapDepth = 1)]
+    names: Vec<T>,
+}
+
+#[derive(Wrap, Debug)]
+#[giftwrap(serde)]
+pub enum Value<T> {
+    Number(i64),
+    Text(String),
+    #[giftwrap(noWrap)]
+    Other {
+        value: T,
+    },
+    List(Vec<Value<T>>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Reading {
+    temperature: Celsius,
+    label: Named<String>,
+    extra: Value<bool>,
+}
+
+fn main() -> Result<(), serde_json::Error> {
+    let reading = Reading {
+        temperature: Celsius::from(21.5),
+        label: Named::from(vec![String::from("kitchen")]),
+        extra: Value::from(vec![Value::from(1), Value::from(String::from("two"))]),
+    };
+    let json = serde_json::to_string(&reading)?;
+    println!("{json}");
+    println!("{:?}", serde_json::from_str::<Reading>(&json)?);
+
+    println!(
+        "{:?}",
+        serde_json::from_str::<Value<bool>>("[3, \"four\"]")?
+    );
+    println!("{}", serde_json::to_string(&Value::Other { value: true })?);
+    println!("{:?}", serde_json::from_str::<Value<bool>>("true").is_err());
+    Ok(())
+}
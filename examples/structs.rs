@@ -1,4 +1,6 @@
 use giftwrap::*;
+use std::collections::HashMap;
+use std::num::Wrapping;
 use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Wrap, Unwrap)]
@@ -12,6 +14,71 @@ pub struct MyNamedStruct {
 #[derive(Debug, Wrap, Unwrap)]
 pub struct Depth(#[giftwrap(wrapDepth = 0)] Arc<Mutex<i32>>);
 
+#[derive(Debug, Wrap)]
+pub struct Shared(#[giftwrap(wrapDepth = 0)] Arc<str>);
+
+#[derive(Debug, Wrap, Unwrap)]
+#[giftwrap(refCast)]
+#[repr(transparent)]
+pub struct UserId(u64);
+
+#[derive(Debug, Wrap, Unwrap, Clone, Copy)]
+#[giftwrap(constNew, constGet = "port", constIntoInner, constVis = "pub(crate)")]
+pub struct Port(u16);
+
+#[derive(Debug, Unwrap, Clone, Copy)]
+#[giftwrap(helpers, constGet = "inner", constIntoInner)]
+pub struct Celsius(f32);
+
+const FREEZING: f32 = Celsius(0.0).into_inner();
+
+const DEFAULT_PORT: Port = Port::new(8080);
+const DEFAULT_PORT_NUMBER: u16 = DEFAULT_PORT.into_inner();
+
+#[derive(Debug, Wrap, Unwrap, Clone, Copy, PartialEq)]
+#[giftwrap(ops = [add, sub, mul_scalar, neg, sum], eq, ord)]
+pub struct Meters(f64);
+
+#[derive(Debug, Wrap, Clone, Copy)]
+#[giftwrap(ops = [bitor, bitand, bitor_scalar, not])]
+pub struct Flags {
+    bits: u32,
+}
+
+#[derive(Debug, Wrap)]
+#[giftwrap(ops = [add, product])]
+pub struct Total<T>(#[giftwrap(wrapDepth = 1)] Wrapping<T>);
+
+#[derive(Debug, Wrap, Default)]
+#[giftwrap(collection)]
+pub struct Tags(#[giftwrap(wrapDepth = 1)] Vec<String>);
+
+#[derive(Debug, Wrap, Default)]
+#[giftwrap(collection)]
+pub struct Registry<K: std::hash::Hash + Eq> {
+    #[giftwrap(wrapDepth = 1)]
+    entries: HashMap<K, u32>,
+}
+
+#[derive(Debug, Unwrap)]
+pub struct Generic<T>(#[giftwrap(intoInner = true)] T);
+
+#[derive(Debug, Wrap)]
+pub struct Wrapper<T>(T);
+
+#[derive(Debug, Unwrap)]
+#[giftwrap(helpers)]
+pub struct Labeled<T>
+where
+    T: std::fmt::Debug,
+{
+    value: T,
+}
+
+#[derive(Debug, Unwrap)]
+#[giftwrap(helpers)]
+pub struct Name(String);
+
 fn main() {
     println!("{:?}", MyStruct::<i64>::from(Some(&12)));
     println!("{:?}", MyStruct::<i64>::from(&13));
@@ -26,4 +93,64 @@ fn main() {
     println!("{:?}", Depth::from(1i32));
     println!("{:?}", Depth::from(Mutex::new(2i32)));
     println!("{:?}", Depth::from(Arc::new(Mutex::new(3i32))));
+
+    println!("{:?}", Shared::from("shared"));
+    let shared = Shared::from(String::from("shared"));
+    assert_eq!(&*shared.0, "shared");
+    println!("{:?}", shared);
+
+    println!("{:?}", Generic(4u8).into_inner());
+    println!("{:?}", Wrapper::from(5u8).0);
+
+    println!("{} {}", Meters(1.0) == 1.0, 2.0 > Meters(1.0));
+
+    let mut distance = Meters(1.5) + Meters(2.0) - Meters(0.5);
+    distance *= 2.0;
+    println!("{:?}", -distance * 0.5);
+    println!(
+        "{:?}",
+        [Meters(1.0), Meters(2.0)].into_iter().sum::<Meters>()
+    );
+    println!(
+        "{:?}",
+        !(Flags::from(1) | Flags::from(2) | 4) & Flags::from(0xff)
+    );
+    let total = (1u8..4)
+        .map(Wrapping)
+        .map(Total::from)
+        .product::<Total<u8>>();
+    println!("{:?}", total + Total::from(Wrapping(25)));
+
+    let mut tags: Tags = ["a", "b"].iter().map(|s| s.to_string()).collect();
+    tags.extend(Some(String::from("c")));
+    tags[0].push('!');
+    for tag in &mut tags {
+        tag.push('?');
+    }
+    println!("{:?} {}", tags, (&tags).into_iter().count());
+    println!("{:?}", tags.into_iter().last());
+
+    let mut registry: Registry<&str> = [("one", 1)].into_iter().collect();
+    registry.extend([("two", 2)]);
+    println!("{}", registry["one"]);
+    println!("{}", registry.into_iter().map(|(_, v)| v).sum::<u32>());
+
+    let mut labeled = Labeled { value: 2u8 };
+    *labeled.inner_mut() += 1;
+    println!("{:?}", labeled.map(|v| v.to_string()).into_inner());
+    let name = Name(String::from("giftwrap"));
+    println!("{} {:?}", name.inner().len(), String::from(name));
+
+    let mut celsius = Celsius(FREEZING);
+    *celsius.inner_mut() += 21.5;
+    println!("{} {}", celsius.inner(), celsius.into_inner());
+
+    println!("{:?} {}", DEFAULT_PORT, DEFAULT_PORT_NUMBER);
+    println!("{}", Port::from(22).port());
+
+    let mut buffer = [1u64, 2, 3];
+    println!("{:?}", UserId::from_slice(&buffer));
+    UserId::from_mut(&mut buffer[0]).0 = 7;
+    println!("{:?}", UserId::from_ref(&buffer[0]));
+    println!("{:?}", UserId::from_mut_slice(&mut buffer)[2]);
 }
@@ -0,0 +1,70 @@
+use giftwrap::*;
+use giftwrap_core::{VariantOf, Wrapper};
+use std::convert::TryFrom;
+
+#[derive(Wrap, Unwrap, Debug, PartialEq)]
+#[giftwrap(traits)]
+pub struct Meters(f64);
+
+#[derive(Wrap, Debug)]
+#[giftwrap(traits)]
+pub struct Named<T> {
+    #[giftwrap(wrapDepth = 1)]
+    inner: Vec<T>,
+}
+
+#[derive(Unwrap, Debug)]
+#[giftwrap(traits)]
+pub enum Value<T> {
+    Int(i64),
+    Other(i64),
+    Text {
+        s: String,
+    },
+    #[giftwrap(intoInner)]
+    Gen(T),
+    #[giftwrap(noUnwrap)]
+    Unit,
+}
+
+fn double<W: Wrapper<Inner = f64>>(w: W) -> W {
+    W::from_inner(w.into_inner() * 2.0)
+}
+
+fn describe<T>(value: &Value<T>) -> &'static str {
+    if value.is::<String>() {
+        "text"
+    } else if value.is::<i64>() {
+        "number"
+    } else {
+        "other"
+    }
+}
+
+fn main() {
+    println!("{:?}", double(Meters(1.5)));
+    println!("{:?}", Named::from_inner(vec![1u8]).into_inner());
+    println!("{:?}", i64::try_from(Value::<()>::Other(3)));
+    let e = String::try_from(Value::<()>::Int(3)).unwrap_err();
+    println!("{e} / {e:?}");
+    println!("{:?}", e.into_value());
+    println!(
+        "{:?}",
+        String::ref_from_enum(&Value::<()>::Text { s: "t".into() })
+    );
+    println!(
+        "{:?}",
+        <i64 as VariantOf<Value<()>>>::ref_from_enum(&Value::Unit)
+    );
+    println!("{:?}", Value::Gen(1u8).try_into_gen());
+    println!(
+        "{:?}",
+        Value::<u8>::Unit.try_into_gen().map_err(|e| e.to_string())
+    );
+
+    println!("{:?}", Value::<()>::Int(5).extract::<i64>());
+    println!("{:?}", Value::<()>::Unit.extract::<String>());
+    println!("{:?}", Value::<()>::Text { s: "t".into() }.get::<String>());
+    println!("{}", describe(&Value::<()>::Other(1)));
+    println!("{}", describe(&Value::Gen(1u8)));
+}
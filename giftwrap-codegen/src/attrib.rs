@@ -0,0 +1,417 @@
+use {
+    crate::case::RenameRule,
+    proc_macro2::{Delimiter, Group, Spacing, Span, TokenStream, TokenTree},
+    serde::{de::DeserializeOwned, Deserialize},
+    syn::{self, parse::Parser, punctuated::Punctuated, Attribute, Token},
+};
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ContainerAttributes {
+    #[serde(default)]
+    pub strings: bool,
+    #[serde(alias = "renameAll", default)]
+    pub rename_all: Option<RenameRule>,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(alias = "kindDerive", default)]
+    kind_derive: Option<Vec<String>>,
+    #[serde(default)]
+    pub reflect: bool,
+    #[serde(default)]
+    pub traits: bool,
+    #[serde(alias = "refCast", default)]
+    pub ref_cast: bool,
+    #[serde(alias = "constNew", default)]
+    pub const_new: Option<Toggle>,
+    #[serde(alias = "constGet", default)]
+    pub const_get: Option<Toggle>,
+    #[serde(alias = "constIntoInner", default)]
+    pub const_into_inner: Option<Toggle>,
+    #[serde(alias = "constVis", default)]
+    const_vis: Option<String>,
+    #[serde(default)]
+    pub helpers: bool,
+    #[serde(default)]
+    pub ops: Vec<Op>,
+    #[serde(default)]
+    pub eq: bool,
+    #[serde(default)]
+    pub ord: bool,
+    #[serde(default)]
+    pub collection: bool,
+    #[serde(default)]
+    pub serde: bool,
+    #[serde(rename = "crate", default)]
+    krate: Option<String>,
+    #[serde(default)]
+    bound: Option<String>,
+}
+
+impl ContainerAttributes {
+    pub(crate) fn load(attrs: &[Attribute]) -> Result<Self, (Span, &'static str)> {
+        load(attrs)
+    }
+
+    /// The name of the kind enum to generate, if any
+    pub(crate) fn kind(&self) -> Result<Option<syn::Ident>, &'static str> {
+        self.kind
+            .as_deref()
+            .map(syn::parse_str)
+            .transpose()
+            .map_err(|_| "`kind` must be an identifier")
+    }
+
+    /// The derives of the kind enum, `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq` and `Hash` unless
+    /// set through `kindDerive`
+    pub(crate) fn kind_derive(&self) -> Result<Vec<syn::Path>, &'static str> {
+        match &self.kind_derive {
+            Some(derives) => derives
+                .iter()
+                .map(|derive| syn::parse_str(derive))
+                .collect::<Result<_, _>>()
+                .map_err(|_| "`kindDerive` must be a list of paths"),
+            None => Ok([
+                "::core::fmt::Debug",
+                "::core::clone::Clone",
+                "::core::marker::Copy",
+                "::core::cmp::PartialEq",
+                "::core::cmp::Eq",
+                "::core::hash::Hash",
+            ]
+            .iter()
+            .map(|derive| syn::parse_str(derive).unwrap())
+            .collect()),
+        }
+    }
+
+    /// The path of `giftwrap_core`, `::giftwrap_core` unless set through `crate` such as when it is
+    /// re-exported by another crate
+    pub(crate) fn krate(&self) -> Result<syn::Path, &'static str> {
+        syn::parse_str(self.krate.as_deref().unwrap_or("::giftwrap_core"))
+            .map_err(|_| "`crate` must be a path such as `my_crate::giftwrap_core`")
+    }
+
+    /// The where predicates set through `bound`, added to every generated impl in place of the
+    /// bounds inferred for the inner type
+    pub(crate) fn bound(&self) -> Result<Option<Vec<syn::WherePredicate>>, &'static str> {
+        parse_bound(&self.bound)
+    }
+
+    /// The visibility of the `const fn`s, `pub` unless set through `constVis`
+    pub(crate) fn const_vis(&self) -> Result<syn::Visibility, &'static str> {
+        syn::parse_str(self.const_vis.as_deref().unwrap_or("pub"))
+            .map_err(|_| "`constVis` must be a visibility such as \"pub(crate)\"")
+    }
+}
+
+/// An operator to forward to the inner type through `ops`, the `Scalar` forms take the inner type
+/// as right-hand side instead of the newtype
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Op {
+    Add,
+    AddScalar,
+    Sub,
+    SubScalar,
+    Mul,
+    MulScalar,
+    Div,
+    DivScalar,
+    Rem,
+    RemScalar,
+    #[serde(rename = "bitand")]
+    BitAnd,
+    #[serde(rename = "bitand_scalar")]
+    BitAndScalar,
+    #[serde(rename = "bitor")]
+    BitOr,
+    #[serde(rename = "bitor_scalar")]
+    BitOrScalar,
+    #[serde(rename = "bitxor")]
+    BitXor,
+    #[serde(rename = "bitxor_scalar")]
+    BitXorScalar,
+    Shl,
+    ShlScalar,
+    Shr,
+    ShrScalar,
+    Neg,
+    Not,
+    Sum,
+    Product,
+}
+
+/// An option that is either switched on with a default name or given a name of its own, such as
+/// `constNew` or `constNew = "with_port"`
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum Toggle {
+    On(bool),
+    Named(String),
+}
+
+impl Toggle {
+    /// The name of the generated item if switched on, `default` unless named
+    pub(crate) fn ident(
+        toggle: &Option<Self>,
+        default: &str,
+    ) -> Result<Option<syn::Ident>, &'static str> {
+        match toggle {
+            None | Some(Self::On(false)) => Ok(None),
+            Some(Self::On(true)) => Ok(Some(syn::Ident::new(default, Span::call_site()))),
+            Some(Self::Named(name)) => syn::parse_str(name)
+                .map(Some)
+                .map_err(|_| "A method name must be an identifier"),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct StructAttributes {
+    #[serde(alias = "wrapDepth", default)]
+    wrap_depth: Option<u32>,
+    #[serde(alias = "intoInner", default)]
+    pub into_inner: bool,
+}
+
+impl StructAttributes {
+    pub(crate) fn load(attrs: &[Attribute]) -> Result<Self, (Span, &'static str)> {
+        load(attrs)
+    }
+
+    pub(crate) fn wrap_depth(&self) -> Option<u32> {
+        match self.wrap_depth {
+            None | Some(0) => None,
+            Some(n) => Some(n),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct VariantAttributes {
+    #[serde(alias = "wrapDepth", default)]
+    wrap_depth: Option<u32>,
+    #[serde(alias = "noWrap", default)]
+    pub no_wrap: bool,
+    #[serde(alias = "noUnwrap", default)]
+    pub no_unwrap: bool,
+    #[serde(alias = "intoInner", default)]
+    pub into_inner: bool,
+    #[serde(alias = "anyImpl", default)]
+    pub any_impl: bool,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    pub alias: Vec<String>,
+    #[serde(alias = "sameAs", default)]
+    same_as: Option<String>,
+    #[serde(default)]
+    bound: Option<String>,
+}
+
+impl VariantAttributes {
+    pub(crate) fn load(attrs: &[Attribute]) -> Result<Self, (Span, &'static str)> {
+        load(attrs)
+    }
+
+    pub(crate) fn wrap_depth(&self) -> Option<u32> {
+        match self.wrap_depth {
+            None | Some(0) => None,
+            Some(n) => Some(n),
+        }
+    }
+
+    /// The string name of `ident`, set through `name` or derived by `rename_all`
+    pub(crate) fn name(&self, ident: &syn::Ident, rename_all: Option<RenameRule>) -> String {
+        match (&self.name, rename_all) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rule.apply(&ident.to_string()),
+            (None, None) => ident.to_string(),
+        }
+    }
+
+    /// The type the inner type is declared identical to through `sameAs`, such as when it is an
+    /// alias of that type
+    pub(crate) fn same_as(&self) -> Result<Option<syn::Type>, &'static str> {
+        self.same_as
+            .as_deref()
+            .map(syn::parse_str)
+            .transpose()
+            .map_err(|_| "`sameAs` must be a type such as `std::string::String`")
+    }
+
+    /// The where predicates set through `bound`, added to the impls generated for the variant in
+    /// place of the bounds inferred for its inner type. Those are dropped as well if `bound` is
+    /// set on the container, which is already part of the generics
+    pub(crate) fn bound(
+        &self,
+        container: &ContainerAttributes,
+    ) -> Result<Option<Vec<syn::WherePredicate>>, &'static str> {
+        Ok(parse_bound(&self.bound)?.or_else(|| container.bound.is_some().then(Vec::new)))
+    }
+}
+
+/// Parses a `bound` such as `"T: Clone, U: Default"`, which may be empty to drop inferred bounds
+fn parse_bound(bound: &Option<String>) -> Result<Option<Vec<syn::WherePredicate>>, &'static str> {
+    bound
+        .as_deref()
+        .map(|bound| {
+            Punctuated::<syn::WherePredicate, Token![,]>::parse_terminated
+                .parse_str(bound)
+                .map(|predicates| predicates.into_iter().collect())
+        })
+        .transpose()
+        .map_err(|_| "`bound` must be a list of where predicates such as \"T: Clone\"")
+}
+
+/// Reads every `#[giftwrap(...)]` in `attrs` as one set of options, an option may only be set once
+fn load<T: DeserializeOwned>(attrs: &[Attribute]) -> Result<T, (Span, &'static str)> {
+    let mut options = toml::value::Table::new();
+    let mut last = Span::call_site();
+    for attr in attrs.iter().filter(|a| a.path.is_ident("giftwrap")) {
+        let group: Group =
+            syn::parse2(attr.tokens.clone()).map_err(|e| (e.span(), "Attr is not a group"))?;
+        if group.delimiter() != Delimiter::Parenthesis {
+            return Err((group.span(), "Attr is not paren delimitered"));
+        }
+        let entries: toml::value::Table = toml::from_str(&to_toml(group.stream()))
+            .map_err(|_| (group.span(), "Attr parse failed"))?;
+        // Read on its own first so an unknown option is pointed out in the attribute it is in
+        if let Err(e) = toml::Value::Table(entries.clone()).try_into::<T>() {
+            let e = e.to_string();
+            return Err(match e.strip_prefix("unknown field `") {
+                Some(rest) => (
+                    key_span(&group, rest.split('`').next().unwrap_or_default()),
+                    "Unknown option, it may belong on the type, a variant or a field instead",
+                ),
+                None => (group.span(), "Attr parse failed"),
+            });
+        }
+        for (key, value) in entries {
+            let span = key_span(&group, &key);
+            if options.insert(key, value).is_some() {
+                return Err((span, "Option is set more than once"));
+            }
+        }
+        last = group.span();
+    }
+    // Options set twice under different names, such as `wrapDepth` and `wrap_depth`, fail here
+    toml::Value::Table(options).try_into().map_err(|e| {
+        match e.to_string().starts_with("duplicate field") {
+            true => (last, "Option is set more than once"),
+            false => (last, "Attr parse failed"),
+        }
+    })
+}
+
+/// The span of option `key` within the attribute `group`, or of `group` if it can't be found
+fn key_span(group: &Group, key: &str) -> Span {
+    split_commas(group.stream())
+        .iter()
+        .filter_map(|entry| entry.first())
+        .find(|token| token.to_string() == key)
+        .map_or_else(|| group.span(), TokenTree::span)
+}
+
+/// Rewrites the contents of an attribute as a TOML table, one `key = value` per line.
+///
+/// A bare `key` is read as `key = true` and values that are neither literals nor arrays, such as
+/// paths and types, are passed along as strings.
+fn to_toml(stream: TokenStream) -> String {
+    split_commas(stream)
+        .iter()
+        .map(|entry| match entry.iter().position(is_eq) {
+            Some(eq) => format!(
+                "{} = {}",
+                TokenStream::from_iter(entry[..eq].iter().cloned()),
+                to_toml_value(&entry[eq + 1..])
+            ),
+            None => format!("{} = true", TokenStream::from_iter(entry.iter().cloned())),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn to_toml_value(tokens: &[TokenTree]) -> String {
+    match tokens {
+        [TokenTree::Literal(lit)] => match syn::parse_str::<syn::LitStr>(&lit.to_string()) {
+            Ok(s) => toml::Value::String(s.value()).to_string(),
+            Err(_) => lit.to_string(),
+        },
+        [TokenTree::Ident(ident)] if ident == "true" || ident == "false" => ident.to_string(),
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => format!(
+            "[{}]",
+            split_commas(group.stream())
+                .iter()
+                .map(|value| to_toml_value(value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => toml::Value::String(TokenStream::from_iter(tokens.iter().cloned()).to_string())
+            .to_string(),
+    }
+}
+
+/// Splits `stream` on the commas outside the generic arguments of any type, so that
+/// `sameAs = HashMap<String, u32>` stays a single entry
+fn split_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut entries = vec![vec![]];
+    let mut depth = 0usize;
+    let mut arrow = false;
+    for token in stream {
+        if let TokenTree::Punct(p) = &token {
+            match p.as_char() {
+                ',' if depth == 0 => {
+                    entries.push(vec![]);
+                    continue;
+                }
+                '<' => depth += 1,
+                // The `>` of `->` closes no generic arguments
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        arrow = match &token {
+            TokenTree::Punct(p) => p.as_char() == '-' && p.spacing() == Spacing::Joint,
+            _ => false,
+        };
+        entries.last_mut().unwrap().push(token);
+    }
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+fn is_eq(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Punct(p) if p.as_char() == '=')
+}
+
+/// The idents of every `#[repr(...)]` hint on a type, such as `u8` or `transparent`
+pub(crate) fn reprs(attrs: &[Attribute]) -> Vec<syn::Ident> {
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("repr"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) => p.get_ident().cloned(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The integer type of a fieldless enum's discriminants, `isize` unless set through `#[repr]`
+pub(crate) fn repr_int(attrs: &[Attribute]) -> syn::Ident {
+    const INTS: [&str; 12] = [
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+    reprs(attrs)
+        .into_iter()
+        .find(|ident| INTS.iter().any(|int| ident == int))
+        .unwrap_or_else(|| syn::Ident::new("isize", Span::call_site()))
+}
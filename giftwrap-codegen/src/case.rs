@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+/// Converts a `PascalCase` identifier such as a variant name into `snake_case`
+pub(crate) fn snake_case(ident: &str) -> String {
+    let mut snake = String::new();
+    for (i, ch) in ident.char_indices() {
+        if i > 0 && ch.is_uppercase() {
+            snake.push('_');
+        }
+        snake.extend(ch.to_lowercase());
+    }
+    snake
+}
+
+/// How `rename_all` turns a `PascalCase` variant name into its string name
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub(crate) enum RenameRule {
+    #[serde(rename = "lowercase")]
+    Lower,
+    #[serde(rename = "UPPERCASE")]
+    Upper,
+    #[serde(rename = "PascalCase")]
+    Pascal,
+    #[serde(rename = "camelCase")]
+    Camel,
+    #[serde(rename = "snake_case")]
+    Snake,
+    #[serde(rename = "SCREAMING_SNAKE_CASE")]
+    ScreamingSnake,
+    #[serde(rename = "kebab-case")]
+    Kebab,
+    #[serde(rename = "SCREAMING-KEBAB-CASE")]
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    pub(crate) fn apply(self, ident: &str) -> String {
+        match self {
+            Self::Lower => ident.to_lowercase(),
+            Self::Upper => ident.to_uppercase(),
+            Self::Pascal => ident.to_owned(),
+            Self::Camel => {
+                let mut chars = ident.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_lowercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Self::Snake => snake_case(ident),
+            Self::ScreamingSnake => snake_case(ident).to_uppercase(),
+            Self::Kebab => snake_case(ident).replace('_', "-"),
+            Self::ScreamingKebab => snake_case(ident).replace('_', "-").to_uppercase(),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use {
+    crate::{attrib::VariantAttributes, get_field},
+    proc_macro2::Span,
+    syn::{self, spanned::Spanned, GenericArgument, PathArguments, Type},
+};
+
+/// The crates `std` re-exports under the same module paths, including the `alloc` brought into
+/// scope by the generated impls
+const STD_CRATES: [&str; 4] = ["std", "core", "alloc", "__giftwrap_alloc"];
+
+/// The types of the prelude that can be named without a path, along with their module
+const PRELUDE: [(&str, &str); 5] = [
+    ("String", "string"),
+    ("Vec", "vec"),
+    ("Box", "boxed"),
+    ("Option", "option"),
+    ("Result", "result"),
+];
+
+/// Decides whether two inner types are the same type rather than merely written the same.
+///
+/// Types are compared after normalizing paths into `std`, `core` and `alloc` as well as the
+/// prelude to the same `std` path, dropping elided lifetimes, turbofishes and parentheses, and
+/// replacing any type declared the same as another through `sameAs`.
+pub(crate) struct TypeIdentity<'g> {
+    generics: &'g syn::Generics,
+    /// Normalized types along with the normalized type they were declared the same as
+    aliases: Vec<(Type, Type)>,
+}
+
+impl<'g> TypeIdentity<'g> {
+    /// The identity of types within `generics` with the `sameAs` of every variant
+    pub(crate) fn new(
+        generics: &'g syn::Generics,
+        variants: &[syn::Variant],
+    ) -> Result<Self, (Span, &'static str)> {
+        let mut identity = Self {
+            generics,
+            aliases: vec![],
+        };
+        for var in variants {
+            let attr = VariantAttributes::load(&var.attrs)?;
+            let same_as = match attr.same_as() {
+                Ok(Some(same_as)) => same_as,
+                Ok(None) => continue,
+                Err(e) => return Err((var.span(), e)),
+            };
+            let field = get_field(&var.fields).map_err(|_| {
+                (
+                    var.span(),
+                    "`sameAs` can only be used on variants with a single field",
+                )
+            })?;
+            let (ty, same_as) = (identity.normalize(&field.ty), identity.normalize(&same_as));
+            if ty != same_as {
+                identity.aliases.push((ty, same_as));
+            }
+        }
+        Ok(identity)
+    }
+
+    /// `ty` written the same as every other type it is identical to
+    pub(crate) fn normalize(&self, ty: &Type) -> Type {
+        let mut ty = self.normalize_parts(ty);
+        // Bounded since aliases could be declared in a cycle
+        for _ in 0..self.aliases.len() {
+            match self.aliases.iter().find(|(alias, _)| *alias == ty) {
+                Some((_, same_as)) => ty = same_as.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    fn normalize_parts(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Paren(p) => self.normalize(&p.elem),
+            Type::Group(g) => self.normalize(&g.elem),
+            Type::Reference(r) => {
+                let mut r = r.clone();
+                if matches!(&r.lifetime, Some(lt) if lt.ident == "_") {
+                    r.lifetime = None;
+                }
+                *r.elem = self.normalize(&r.elem);
+                Type::Reference(r)
+            }
+            Type::Ptr(p) => {
+                let mut p = p.clone();
+                *p.elem = self.normalize(&p.elem);
+                Type::Ptr(p)
+            }
+            Type::Slice(s) => {
+                let mut s = s.clone();
+                *s.elem = self.normalize(&s.elem);
+                Type::Slice(s)
+            }
+            Type::Array(a) => {
+                let mut a = a.clone();
+                *a.elem = self.normalize(&a.elem);
+                Type::Array(a)
+            }
+            Type::Tuple(t) => {
+                let mut t = t.clone();
+                t.elems = t.elems.iter().map(|ty| self.normalize(ty)).collect();
+                Type::Tuple(t)
+            }
+            Type::BareFn(f) => {
+                let mut f = f.clone();
+                for arg in &mut f.inputs {
+                    arg.ty = self.normalize(&arg.ty);
+                }
+                if let syn::ReturnType::Type(_, ty) = &mut f.output {
+                    **ty = self.normalize(ty);
+                }
+                Type::BareFn(f)
+            }
+            Type::Path(p) => {
+                let mut p = p.clone();
+                match &mut p.qself {
+                    Some(qself) => *qself.ty = self.normalize(&qself.ty),
+                    None => self.normalize_path(&mut p.path),
+                }
+                for seg in &mut p.path.segments {
+                    if let PathArguments::AngleBracketed(brac) = &mut seg.arguments {
+                        brac.colon2_token = None;
+                        brac.args = brac
+                            .args
+                            .iter()
+                            .filter(|arg| {
+                                !matches!(arg, GenericArgument::Lifetime(lt) if lt.ident == "_")
+                            })
+                            .map(|arg| match arg {
+                                GenericArgument::Type(ty) => GenericArgument::Type(self.normalize(ty)),
+                                arg => arg.clone(),
+                            })
+                            .collect();
+                        if brac.args.is_empty() {
+                            seg.arguments = PathArguments::None;
+                        }
+                    }
+                }
+                Type::Path(p)
+            }
+            ty => ty.clone(),
+        }
+    }
+
+    /// Rewrites paths into `core` and `alloc` as `std`, prelude types as their full `std` path and
+    /// `std::primitive` types as the bare primitive
+    fn normalize_path(&self, path: &mut syn::Path) {
+        let first = path.segments[0].ident.clone();
+        if STD_CRATES.iter().any(|krate| first == krate) && path.segments.len() > 1 {
+            path.leading_colon = None;
+            path.segments[0].ident = syn::Ident::new("std", first.span());
+            if path.segments.len() == 3 && path.segments[1].ident == "primitive" {
+                path.segments = path.segments.iter().skip(2).cloned().collect();
+            }
+        } else if path.leading_colon.is_none()
+            && path.segments.len() == 1
+            && self
+                .generics
+                .type_params()
+                .all(|param| param.ident != first)
+        {
+            if let Some((_, module)) = PRELUDE.iter().find(|(ty, _)| first == ty) {
+                let last = path.segments.pop().unwrap().into_value();
+                let span = last.ident.span();
+                path.segments.push(syn::Ident::new("std", span).into());
+                path.segments.push(syn::Ident::new(module, span).into());
+                path.segments.push(last);
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! The code generation behind the [`giftwrap`](https://docs.rs/giftwrap) derives as a regular
+//! library, for build scripts and other macros that write Rust code of their own.
+//!
+//! Given a type definition, [`derive_wrap`] and [`derive_unwrap`] return the same impls as
+//! `#[derive(Wrap)]` and `#[derive(Unwrap)]` would, reading any `#[giftwrap(...)]` attributes on
+//! it. The definition itself is not part of the output and doesn't need to carry the derives, so a
+//! schema compiler can write a type and its conversions side by side once [`strip_attrs`] has
+//! removed the attributes only the derives know about:
+//!
+//! ```ignore
+//! // build.rs
+//! let mut input: syn::DeriveInput =
+//!     syn::parse_str("#[giftwrap(traits)] pub enum Message { Ping(Ping), Text(String) }")?;
+//! let wrap = giftwrap_codegen::derive_wrap(&input)?;
+//! let unwrap = giftwrap_codegen::derive_unwrap(&input)?;
+//! giftwrap_codegen::strip_attrs(&mut input);
+//! std::fs::write(out_dir.join("message.rs"), quote::quote!(#input #wrap #unwrap).to_string())?;
+//! ```
+//!
+//! Errors are returned rather than emitted as `compile_error!`, with spans pointing into the
+//! definition.
+use harled::{Error, FromDeriveInput, Kind};
+use proc_macro2::TokenStream;
+
+#[macro_use]
+mod wrap;
+#[macro_use]
+mod unwrap;
+
+pub(crate) mod attrib;
+pub(crate) mod case;
+pub(crate) mod identity;
+
+/// The impls of `#[derive(Wrap)]` for `input`
+pub fn derive_wrap(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    wrap::Derive::from_derive_input(input)
+        .map_err(|e| match e {
+            Error::Unsupported(Kind::Union, span) => wrap::Error::For(span, "Union").into(),
+            Error::Syn(syn) => syn,
+            _ => unreachable!(),
+        })?
+        .derive()
+}
+
+/// The impls of `#[derive(Unwrap)]` for `input`
+pub fn derive_unwrap(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    unwrap::Derive::from_derive_input(input)
+        .map_err(|e| match e {
+            Error::Unsupported(Kind::Union, span) => unwrap::Error::For(span, "Union").into(),
+            Error::Syn(syn) => syn,
+            _ => unreachable!(),
+        })?
+        .derive()
+}
+
+/// The impls of `#[derive(Wrap)]` for the type definition in `source`
+pub fn derive_wrap_str(source: &str) -> syn::Result<TokenStream> {
+    derive_wrap(&syn::parse_str(source)?)
+}
+
+/// The impls of `#[derive(Unwrap)]` for the type definition in `source`
+pub fn derive_unwrap_str(source: &str) -> syn::Result<TokenStream> {
+    derive_unwrap(&syn::parse_str(source)?)
+}
+
+/// Removes every `#[giftwrap(...)]` attribute from `input` along with its variants and fields, which
+/// the compiler only accepts on a type deriving `Wrap` or `Unwrap`
+pub fn strip_attrs(input: &mut syn::DeriveInput) {
+    fn strip(attrs: &mut Vec<syn::Attribute>) {
+        attrs.retain(|attr| !attr.path.is_ident("giftwrap"));
+    }
+    fn strip_fields(fields: &mut syn::Fields) {
+        fields.iter_mut().for_each(|field| strip(&mut field.attrs));
+    }
+
+    strip(&mut input.attrs);
+    match &mut input.data {
+        syn::Data::Struct(s) => strip_fields(&mut s.fields),
+        syn::Data::Enum(e) => e.variants.iter_mut().for_each(|var| {
+            strip(&mut var.attrs);
+            strip_fields(&mut var.fields);
+        }),
+        syn::Data::Union(u) => u
+            .fields
+            .named
+            .iter_mut()
+            .for_each(|field| strip(&mut field.attrs)),
+    }
+}
+
+pub(crate) enum GetFieldError {
+    Unit(proc_macro2::Span),
+    NotSingle(proc_macro2::Span),
+}
+
+/// The member `field` is accessed through, its name or `0` if it is the field of a tuple struct
+pub(crate) fn field_member(field: &syn::Field) -> syn::Member {
+    match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(0.into()),
+    }
+}
+
+pub(crate) fn get_field(fields: &syn::Fields) -> Result<&syn::Field, GetFieldError> {
+    use syn::spanned::Spanned;
+    match fields {
+        syn::Fields::Named(f) => {
+            if f.named.len() != 1 {
+                Err(GetFieldError::NotSingle(f.brace_token.span))
+            } else {
+                Ok(f.named.first().unwrap())
+            }
+        }
+        syn::Fields::Unnamed(f) => {
+            if f.unnamed.len() != 1 {
+                Err(GetFieldError::NotSingle(f.paren_token.span))
+            } else {
+                Ok(f.unnamed.first().unwrap())
+            }
+        }
+        syn::Fields::Unit => Err(GetFieldError::Unit(fields.span())),
+    }
+}
+
+/// Returns the ident of `ty` if it is nothing but one of the type parameters in `generics`
+pub(crate) fn generic_param<'a>(
+    ty: &'a syn::Type,
+    generics: &syn::Generics,
+) -> Option<&'a syn::Ident> {
+    match ty {
+        syn::Type::Path(p) if p.qself.is_none() => {
+            let ident = p.path.get_ident()?;
+            generics
+                .type_params()
+                .any(|param| &param.ident == ident)
+                .then_some(ident)
+        }
+        syn::Type::Paren(p) => generic_param(&p.elem, generics),
+        syn::Type::Group(g) => generic_param(&g.elem, generics),
+        _ => None,
+    }
+}
+
+/// Whether every variant is a unit variant, making the enum fieldless
+pub(crate) fn is_fieldless<'a>(variants: impl IntoIterator<Item = &'a syn::Variant>) -> bool {
+    let mut variants = variants.into_iter().peekable();
+    variants.peek().is_some() && variants.all(|var| matches!(var.fields, syn::Fields::Unit))
+}
+
+/// A type parameter named `base`, or `base1`, `base2`, ... if `generics` already has one by that name
+pub(crate) fn fresh_param(generics: &syn::Generics, base: &str) -> syn::Ident {
+    (0usize..)
+        .map(|i| match i {
+            0 => quote::format_ident!("{}", base),
+            i => quote::format_ident!("{}{}", base, i),
+        })
+        .find(|param| generics.type_params().all(|t| &t.ident != param))
+        .unwrap()
+}
+
+/// A lifetime named `'base`, or `'base1`, `'base2`, ... if `generics` already has one by that name
+pub(crate) fn fresh_lifetime(generics: &syn::Generics, base: &str) -> syn::Lifetime {
+    (0usize..)
+        .map(|i| match i {
+            0 => syn::Lifetime::new(&format!("'{base}"), proc_macro2::Span::call_site()),
+            i => syn::Lifetime::new(&format!("'{base}{i}"), proc_macro2::Span::call_site()),
+        })
+        .find(|lt| generics.lifetimes().all(|l| &l.lifetime != lt))
+        .unwrap()
+}
+
+/// `generics` with `predicates` added to its where clause
+pub(crate) fn bounded(
+    generics: &syn::Generics,
+    predicates: impl IntoIterator<Item = syn::WherePredicate>,
+) -> syn::Generics {
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_from_derive_input() {
+        let input =
+            syn::parse_str("#[giftwrap(traits)] pub enum Message { Ping(u8), Text(String) }")
+                .unwrap();
+        let wrap = derive_wrap(&input).unwrap().to_string();
+        let unwrap = derive_unwrap(&input).unwrap().to_string();
+        assert!(wrap.contains("From < u8 > for Message"), "{wrap}");
+        assert!(unwrap.contains("TryFrom < Message > for u8"), "{unwrap}");
+    }
+
+    #[test]
+    fn rejects_union() {
+        let input = syn::parse_str("union Bits { int: u32, float: f32 }").unwrap();
+        assert_eq!(
+            derive_wrap(&input).unwrap_err().to_string(),
+            "Wrap cannot be derived for Union"
+        );
+        assert_eq!(
+            derive_unwrap(&input).unwrap_err().to_string(),
+            "Unwrap cannot be derived for Union"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_attributes() {
+        assert!(derive_wrap_str("struct Deep(#[giftwrap(wrapDepth = \"deep\")] u8);").is_err());
+        assert!(derive_unwrap_str("struct Two(u8, u16);").is_err());
+        assert!(derive_unwrap_str("not a type").is_err());
+        assert!(derive_wrap_str("#[giftwrap(refcast)] struct Id(u64);").is_err());
+        assert!(derive_wrap_str("#[giftwrap(eq)] #[giftwrap(eq)] struct Id(u64);").is_err());
+        assert!(derive_wrap_str("#[giftwrap(eq)] #[giftwrap(constNew)] struct Id(u64);").is_ok());
+    }
+
+    #[test]
+    fn strips_attributes() {
+        let mut input = syn::parse_str(
+            "#[derive(Debug)] #[giftwrap(helpers)] struct Name(#[giftwrap(intoInner)] String);",
+        )
+        .unwrap();
+        strip_attrs(&mut input);
+        assert_eq!(
+            quote::quote!(#input).to_string(),
+            "# [derive (Debug)] struct Name (String) ;"
+        );
+    }
+}
@@ -0,0 +1,1005 @@
+use {
+    crate::{
+        attrib::{repr_int, ContainerAttributes, StructAttributes, Toggle, VariantAttributes},
+        bounded,
+        case::snake_case,
+        field_member, fresh_param, generic_param, get_field,
+        identity::TypeIdentity,
+        is_fieldless, GetFieldError,
+    },
+    harled::FromDeriveInput,
+    proc_macro2::{Span, TokenStream},
+    quote::{format_ident, quote, ToTokens},
+    syn::{parse_quote, punctuated::Punctuated, spanned::Spanned, token},
+};
+
+pub(crate) enum Error {
+    For(Span, &'static str),
+    Only(Span, &'static str),
+    Special(Span, &'static str),
+}
+
+impl From<Error> for syn::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::For(span, msg) => {
+                syn::Error::new(span, format!("Unwrap cannot be derived for {msg}"))
+            }
+            Error::Only(span, msg) => {
+                syn::Error::new(span, format!("Unwrap can only be derived for {msg}"))
+            }
+            Error::Special(span, msg) => syn::Error::new(span, msg),
+        }
+    }
+}
+
+impl From<GetFieldError> for Error {
+    fn from(e: GetFieldError) -> Self {
+        match e {
+            GetFieldError::Unit(span) => Error::For(span, "Unit variant"),
+            GetFieldError::NotSingle(span) => Error::Only(span, "variant with 1 field"),
+        }
+    }
+}
+
+#[derive(FromDeriveInput, Debug)]
+pub(crate) enum Derive {
+    Struct(Struct),
+    Enum(Enum),
+}
+
+impl Derive {
+    pub(crate) fn derive(self) -> syn::Result<TokenStream> {
+        match self {
+            Self::Struct(s) => s.derive(),
+            Self::Enum(e) => e.derive(),
+        }
+        .map_err(syn::Error::from)
+    }
+}
+
+#[derive(FromDeriveInput, Debug)]
+#[harled(Struct)]
+pub(crate) struct Struct {
+    ident: syn::Ident,
+    attrs: Vec<syn::Attribute>,
+    generics: syn::Generics,
+    fields: syn::Fields,
+}
+
+impl Struct {
+    fn derive(self) -> Result<TokenStream, Error> {
+        let Self {
+            ident,
+            attrs,
+            generics,
+            fields,
+        } = self;
+
+        let (fields, err_span): (&Punctuated<syn::Field, token::Comma>, proc_macro2::Span) =
+            match &fields {
+                syn::Fields::Named(f) => (&f.named, f.brace_token.span),
+                syn::Fields::Unnamed(f) => (&f.unnamed, f.paren_token.span),
+                syn::Fields::Unit => {
+                    return Err(Error::For(ident.span(), "Unit struct"));
+                }
+            };
+
+        if fields.len() != 1 {
+            return Err(Error::Only(err_span, "struct with 1 field"));
+        }
+
+        let field: &syn::Field = fields.first().unwrap();
+        let attr =
+            StructAttributes::load(&field.attrs).map_err(|(span, e)| Error::Special(span, e))?;
+        let ty: &syn::Type = &field.ty;
+        let container =
+            ContainerAttributes::load(&attrs).map_err(|(span, e)| Error::Special(span, e))?;
+        let bound = container
+            .bound()
+            .map_err(|e| Error::Special(ident.span(), e))?;
+        let infer = bound.is_none();
+        let generics = bounded(&generics, bound.into_iter().flatten());
+        let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+
+        let mut stream = derive_const_fns(&container, &ident, &generics, field)?;
+        let const_fns = const_fn_names(&container).map_err(|e| Error::Special(ident.span(), e))?;
+        if container.helpers {
+            stream.extend(derive_helpers(&ident, &generics, field, &const_fns));
+        }
+        if container.eq || container.ord {
+            stream.extend(derive_cmp(&container, &ident, &generics, field, infer)?);
+        }
+
+        if attr.into_inner {
+            if container.helpers || const_fns.iter().any(|name| name == "into_inner") {
+                return Ok(stream);
+            }
+            let member = field_member(field);
+            stream.extend(quote! {
+                impl #impl_gen #ident #ty_gen #where_clause {
+                    #[inline]
+                    pub fn into_inner(self) -> #ty {
+                        self.#member
+                    }
+                }
+            });
+            return Ok(stream);
+        }
+
+        if generic_param(ty, &generics).is_some() {
+            // `From<Self> for T` would break the orphan rule, the `into_inner` helper takes its place
+            if container.helpers {
+                return Ok(stream);
+            }
+            return Err(Error::Special(
+                ty.span(),
+                concat!(
+                    "Unwrap cannot be derived for a bare generic type without breaking the orphan rule (E0210)\n",
+                    "\tConsider using `intoInner` here, or `helpers` on the struct"
+                ),
+            ));
+        }
+
+        let member = field_member(field);
+        stream.extend(quote! {
+            #[automatically_derived]
+            impl #impl_gen ::core::convert::From<#ident #ty_gen> for #ty #where_clause {
+                #[inline]
+                fn from(__value: #ident #ty_gen) -> Self {
+                    __value.#member
+                }
+            }
+        });
+        Ok(stream)
+    }
+}
+
+/// Derives `PartialEq` and `PartialOrd` between a struct and its inner type, both ways, bounded on
+/// the inner type implementing them if `infer` is set
+fn derive_cmp(
+    container: &ContainerAttributes,
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field: &syn::Field,
+    infer: bool,
+) -> Result<TokenStream, Error> {
+    let ty = &field.ty;
+    if generic_param(ty, generics).is_some() {
+        return Err(Error::Special(
+            ty.span(),
+            "`eq` and `ord` cannot be used on a bare generic type since `PartialEq<T>` would conflict with `PartialEq<Self>`",
+        ));
+    }
+    let member = field_member(field);
+    let (_, ty_gen, _) = generics.split_for_impl();
+
+    let mut stream = TokenStream::new();
+    let mut cmp = |bound: TokenStream, body: TokenStream, rev_body: TokenStream| {
+        let generics = bounded(generics, infer.then(|| parse_quote!(#ty: #bound)));
+        let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+        stream.extend(quote! {
+            #[automatically_derived]
+            impl #impl_gen #bound<#ty> for #ident #ty_gen #where_clause {
+                #body
+            }
+
+            #[automatically_derived]
+            impl #impl_gen #bound<#ident #ty_gen> for #ty #where_clause {
+                #rev_body
+            }
+        });
+    };
+
+    if container.eq {
+        cmp(
+            quote!(::core::cmp::PartialEq),
+            quote! {
+                #[inline]
+                fn eq(&self, __other: &#ty) -> ::core::primitive::bool {
+                    ::core::cmp::PartialEq::eq(&self.#member, __other)
+                }
+            },
+            quote! {
+                #[inline]
+                fn eq(&self, __other: &#ident #ty_gen) -> ::core::primitive::bool {
+                    ::core::cmp::PartialEq::eq(self, &__other.#member)
+                }
+            },
+        );
+    }
+    if container.ord {
+        cmp(
+            quote!(::core::cmp::PartialOrd),
+            quote! {
+                #[inline]
+                fn partial_cmp(&self, __other: &#ty) -> ::core::option::Option<::core::cmp::Ordering> {
+                    ::core::cmp::PartialOrd::partial_cmp(&self.#member, __other)
+                }
+            },
+            quote! {
+                #[inline]
+                fn partial_cmp(&self, __other: &#ident #ty_gen) -> ::core::option::Option<::core::cmp::Ordering> {
+                    ::core::cmp::PartialOrd::partial_cmp(self, &__other.#member)
+                }
+            },
+        );
+    }
+    Ok(stream)
+}
+
+/// Derives the inherent `into_inner`, `inner` and `inner_mut` of a struct, and `map` if its field
+/// is a bare type parameter, leaving out any already named in `taken`
+fn derive_helpers(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field: &syn::Field,
+    taken: &[syn::Ident],
+) -> TokenStream {
+    let free = |name: &str| !taken.iter().any(|taken| taken == name);
+    let ty = &field.ty;
+    let member = field_member(field);
+
+    let map = generic_param(ty, generics).filter(|_| free("map")).map(|param| {
+        let mapped = fresh_param(generics, "U");
+        let args = generics.params.iter().map(|p| match p {
+            syn::GenericParam::Type(t) if &t.ident == param => mapped.to_token_stream(),
+            syn::GenericParam::Type(t) => t.ident.to_token_stream(),
+            syn::GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+            syn::GenericParam::Const(c) => c.ident.to_token_stream(),
+        });
+        // `U` has to meet the same bounds as the parameter it replaces
+        let inline = generics.type_params().find(|t| &t.ident == param).unwrap();
+        let mut bounds: Vec<_> = inline.bounds.iter().map(|b| quote!(#mapped: #b)).collect();
+        bounds.extend(
+            generics
+                .where_clause
+                .iter()
+                .flat_map(|w| &w.predicates)
+                .filter_map(|pred| match pred {
+                    syn::WherePredicate::Type(t)
+                        if generic_param(&t.bounded_ty, generics) == Some(param) =>
+                    {
+                        let b = &t.bounds;
+                        Some(quote!(#mapped: #b))
+                    }
+                    _ => None,
+                }),
+        );
+        let from_mapped = match &field.ident {
+            Some(field) => quote!(#ident { #field: __f(self.#field) }),
+            None => quote!(#ident(__f(self.0))),
+        };
+        quote! {
+            /// Maps the inner value with `f`
+            #[inline]
+            pub fn map<#mapped>(self, __f: impl ::core::ops::FnOnce(#ty) -> #mapped) -> #ident<#(#args),*>
+            where
+                #(#bounds,)*
+            {
+                #from_mapped
+            }
+        }
+    });
+
+    let into_inner = free("into_inner").then(|| {
+        quote! {
+            /// Unwraps the inner value
+            #[inline]
+            pub fn into_inner(self) -> #ty {
+                self.#member
+            }
+        }
+    });
+    let inner = free("inner").then(|| {
+        quote! {
+            /// Borrows the inner value
+            #[inline]
+            pub fn inner(&self) -> &#ty {
+                &self.#member
+            }
+        }
+    });
+    let inner_mut = free("inner_mut").then(|| {
+        quote! {
+            /// Mutably borrows the inner value
+            #[inline]
+            pub fn inner_mut(&mut self) -> &mut #ty {
+                &mut self.#member
+            }
+        }
+    });
+
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_gen #ident #ty_gen #where_clause {
+            #into_inner
+            #inner
+            #inner_mut
+            #map
+        }
+    }
+}
+
+/// The names of the `const fn` accessors `constGet` and `constIntoInner` of a struct
+fn const_fn_names(container: &ContainerAttributes) -> Result<Vec<syn::Ident>, &'static str> {
+    Ok([
+        Toggle::ident(&container.const_get, "get")?,
+        Toggle::ident(&container.const_into_inner, "into_inner")?,
+    ]
+    .into_iter()
+    .flatten()
+    .collect())
+}
+
+/// Derives the `const fn` accessors `constGet` and `constIntoInner` of a struct
+fn derive_const_fns(
+    container: &ContainerAttributes,
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field: &syn::Field,
+) -> Result<TokenStream, Error> {
+    let get =
+        Toggle::ident(&container.const_get, "get").map_err(|e| Error::Special(ident.span(), e))?;
+    let into_inner = Toggle::ident(&container.const_into_inner, "into_inner")
+        .map_err(|e| Error::Special(ident.span(), e))?;
+    if get.is_none() && into_inner.is_none() {
+        return Ok(TokenStream::new());
+    }
+
+    let vis = container
+        .const_vis()
+        .map_err(|e| Error::Special(ident.span(), e))?;
+    let ty = &field.ty;
+    if into_inner.is_some() && has_destructor(ty) {
+        return Err(Error::Special(
+            ty.span(),
+            concat!(
+                "`constIntoInner` cannot be used on an inner type with a destructor, which a `const fn` cannot run\n",
+                "\tConsider using `helpers` here"
+            ),
+        ));
+    }
+    let member = field_member(field);
+    let get = get.map(|get| {
+        quote! {
+            #[inline]
+            #vis const fn #get(&self) -> &#ty {
+                &self.#member
+            }
+        }
+    });
+    let into_inner = into_inner.map(|into_inner| {
+        quote! {
+            #[inline]
+            #vis const fn #into_inner(self) -> #ty {
+                self.#member
+            }
+        }
+    });
+
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_gen #ident #ty_gen #where_clause {
+            #get
+            #into_inner
+        }
+    })
+}
+
+/// Whether `ty` is known to have a destructor, which rules out moving it out of `self` in a
+/// `const fn` (E0493). Only `std` types are known, any other type is assumed to have none.
+fn has_destructor(ty: &syn::Type) -> bool {
+    const DROP: [&str; 17] = [
+        "String",
+        "Vec",
+        "Box",
+        "Rc",
+        "Arc",
+        "HashMap",
+        "HashSet",
+        "BTreeMap",
+        "BTreeSet",
+        "VecDeque",
+        "BinaryHeap",
+        "LinkedList",
+        "OsString",
+        "PathBuf",
+        "CString",
+        "Mutex",
+        "RwLock",
+    ];
+    match ty {
+        syn::Type::Path(p) if p.qself.is_none() => {
+            let last = p.path.segments.last().unwrap();
+            if DROP.iter().any(|d| last.ident == d) {
+                return true;
+            }
+            // Only containers that hold their arguments by value are looked into
+            if last.ident != "Option" && last.ident != "Result" {
+                return false;
+            }
+            match &last.arguments {
+                syn::PathArguments::AngleBracketed(brac) => brac.args.iter().any(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => has_destructor(ty),
+                    _ => false,
+                }),
+                _ => false,
+            }
+        }
+        syn::Type::Tuple(t) => t.elems.iter().any(has_destructor),
+        syn::Type::Array(a) => has_destructor(&a.elem),
+        syn::Type::Paren(p) => has_destructor(&p.elem),
+        syn::Type::Group(g) => has_destructor(&g.elem),
+        _ => false,
+    }
+}
+
+#[derive(FromDeriveInput, Debug)]
+#[harled(Enum)]
+pub(crate) struct Enum {
+    ident: syn::Ident,
+    vis: syn::Visibility,
+    attrs: Vec<syn::Attribute>,
+    generics: syn::Generics,
+    variants: Vec<syn::Variant>,
+}
+
+impl Enum {
+    fn derive(self) -> Result<TokenStream, Error> {
+        let Self {
+            ident: name,
+            vis,
+            attrs,
+            generics,
+            variants,
+        } = self;
+
+        let container =
+            ContainerAttributes::load(&attrs).map_err(|(span, e)| Error::Special(span, e))?;
+        let bound = container
+            .bound()
+            .map_err(|e| Error::Special(name.span(), e))?;
+        let generics = bounded(&generics, bound.into_iter().flatten());
+        let mut stream = match container
+            .kind()
+            .and_then(|kind| Ok((kind, container.kind_derive()?)))
+            .map_err(|e| Error::Special(name.span(), e))?
+        {
+            (Some(kind), derives) => {
+                derive_kind(&name, &vis, &generics, &variants, &kind, &derives)
+            }
+            (None, _) => TokenStream::new(),
+        };
+        if container.reflect {
+            stream.extend(derive_reflect(&name, &vis, &generics, &variants)?);
+        }
+
+        if container.ord {
+            return Err(Error::Special(
+                name.span(),
+                "`ord` can only be used on structs",
+            ));
+        }
+        if container.eq && is_fieldless(&variants) {
+            return Err(Error::Special(
+                name.span(),
+                "`eq` can only be used on structs and enums with fields",
+            ));
+        }
+
+        if is_fieldless(&variants) {
+            let repr = repr_int(&attrs);
+            let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+            stream.extend(quote! {
+                #[automatically_derived]
+                impl #impl_gen ::core::convert::From<#name #ty_gen> for ::core::primitive::#repr #where_clause {
+                    #[inline]
+                    fn from(__value: #name #ty_gen) -> Self {
+                        __value as ::core::primitive::#repr
+                    }
+                }
+            });
+            if container.strings {
+                let arms = variants
+                    .iter()
+                    .map(|var| {
+                        let attr = VariantAttributes::load(&var.attrs)
+                            .map_err(|(span, e)| Error::Special(span, e))?;
+                        let varname = &var.ident;
+                        let var_str = attr.name(varname, container.rename_all);
+                        Ok(quote! {
+                            #name::#varname => #var_str,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                stream.extend(quote! {
+                    #[automatically_derived]
+                    impl #impl_gen ::core::convert::From<#name #ty_gen> for &'static ::core::primitive::str #where_clause {
+                        #[inline]
+                        fn from(__value: #name #ty_gen) -> Self {
+                            match __value {
+                                #(#arms)*
+                            }
+                        }
+                    }
+                });
+            }
+            return Ok(stream);
+        }
+        if container.strings {
+            return Err(Error::Special(
+                name.span(),
+                "`strings` can only be used on fieldless enums",
+            ));
+        }
+
+        // Grouped by inner type as normalized, both in declaration order so the expansion is the
+        // same every run. A group is bounded on the `bound` of all its variants if any has one
+        let identity =
+            TypeIdentity::new(&generics, &variants).map_err(|(span, e)| Error::Special(span, e))?;
+        let mut wraps: Vec<(&syn::Type, syn::Type, Vec<&syn::Variant>, Option<Vec<_>>)> = vec![];
+        let mut into_inners = vec![];
+
+        for res in variants
+            .iter()
+            .filter_map(|var| match VariantAttributes::load(&var.attrs) {
+                Ok(attr) => (!attr.no_unwrap).then_some(Ok((var, attr))),
+                Err((span, e)) => Some(Err(Error::Special(span, e))),
+            })
+        {
+            let (var, attr) = res?;
+            let field = get_field(&var.fields)?;
+            let ty: &syn::Type = &field.ty;
+            let bound = attr
+                .bound(&container)
+                .map_err(|e| Error::Special(var.span(), e))?;
+            if attr.into_inner {
+                into_inners.push((var, ty, bound));
+                continue;
+            }
+            if generic_param(ty, &generics).is_some() {
+                return Err(Error::Special(
+                    ty.span(),
+                    concat!(
+                        "Unwrap cannot be derived for a bare generic type without breaking the orphan rule (E0210)\n",
+                        "\tConsider using `noUnwrap` or `intoInner` here"
+                    ),
+                ));
+            }
+            let normalized = identity.normalize(ty);
+            match wraps
+                .iter_mut()
+                .find(|(_, wrapped, ..)| *wrapped == normalized)
+            {
+                Some((_, _, vars, group_bound)) => {
+                    vars.push(var);
+                    if let Some(bound) = bound {
+                        group_bound.get_or_insert_with(Vec::new).extend(bound);
+                    }
+                }
+                None => wraps.push((ty, normalized, vec![var], bound)),
+            }
+        }
+
+        let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+        let krate = container
+            .krate()
+            .map_err(|e| Error::Special(name.span(), e))?;
+        let traits = container.traits.then_some(&krate);
+        let err_ty = match traits {
+            Some(krate) => quote!(#krate::UnwrapError<#name #ty_gen>),
+            None => quote!(&'static ::core::primitive::str),
+        };
+
+        // Every impl gets a single fallback arm for the variants it doesn't unwrap, sharing the
+        // helpers of `derive_fallback` so the expansion stays linear in the number of variants
+        let fallback = |ty: &syn::Type| fallback_arm(&name, &generics, ty, traits);
+        let mut unwraps = TokenStream::new();
+        let mut needs_fallback = false;
+
+        for (ty, _, vars, bound) in &wraps {
+            let wrap_generics = bounded(&generics, bound.iter().flatten().cloned());
+            let (impl_gen, _, where_clause) = wrap_generics.split_for_impl();
+            let match_arms: Vec<_> = vars
+                .iter()
+                .map(|var| match_arm(&name, var))
+                .collect::<Result<Vec<_>, GetFieldError>>()?;
+            let has_others = vars.len() < variants.len();
+            needs_fallback |= has_others;
+            let fallback_arm = has_others.then(|| fallback(ty));
+            unwraps.extend::<TokenStream>(quote! {
+                #[automatically_derived]
+                impl #impl_gen ::core::convert::TryFrom<#name #ty_gen> for #ty #where_clause {
+                    type Error = #err_ty;
+
+                    #[inline]
+                    fn try_from(__value: #name #ty_gen) -> ::core::result::Result<Self, Self::Error> {
+                        match __value {
+                            #(#match_arms)*
+                            #fallback_arm
+                        }
+                    }
+                }
+            });
+
+            // The variants holding `ty` with their value bound to `__inner`
+            let binds = vars
+                .iter()
+                .map(|var| {
+                    let varname = &var.ident;
+                    Ok(match &get_field(&var.fields)?.ident {
+                        Some(ident) => quote!(#name::#varname{ #ident: __inner }),
+                        None => quote!(#name::#varname(__inner)),
+                    })
+                })
+                .collect::<Result<Vec<_>, GetFieldError>>()?;
+            let other_arm = |value: TokenStream| has_others.then(|| quote!(_ => #value,));
+
+            if container.traits {
+                let none_arm = other_arm(quote!(::core::option::Option::None));
+                unwraps.extend::<TokenStream>(quote! {
+                    #[automatically_derived]
+                    impl #impl_gen #krate::VariantOf<#name #ty_gen> for #ty #where_clause {
+                        #[inline]
+                        fn try_from_enum(__value: #name #ty_gen) -> ::core::result::Result<Self, #err_ty> {
+                            <Self as ::core::convert::TryFrom<#name #ty_gen>>::try_from(__value)
+                        }
+
+                        #[inline]
+                        fn ref_from_enum(__value: &#name #ty_gen) -> ::core::option::Option<&Self> {
+                            match __value {
+                                #(#binds => ::core::option::Option::Some(__inner),)*
+                                #none_arm
+                            }
+                        }
+                    }
+                });
+            }
+
+            if container.eq {
+                let eq_generics = bounded(
+                    &generics,
+                    match bound {
+                        Some(bound) => bound.clone(),
+                        None => vec![parse_quote!(#ty: ::core::cmp::PartialEq)],
+                    },
+                );
+                let (eq_impl_gen, _, eq_where_clause) = eq_generics.split_for_impl();
+                let false_arm = other_arm(quote!(false));
+                unwraps.extend::<TokenStream>(quote! {
+                    #[automatically_derived]
+                    impl #eq_impl_gen ::core::cmp::PartialEq<#ty> for #name #ty_gen #eq_where_clause {
+                        #[inline]
+                        fn eq(&self, __other: &#ty) -> ::core::primitive::bool {
+                            match self {
+                                #(#binds => ::core::cmp::PartialEq::eq(__inner, __other),)*
+                                #false_arm
+                            }
+                        }
+                    }
+                });
+            }
+        }
+
+        if container.traits {
+            let param = fresh_param(&generics, "T");
+            unwraps.extend::<TokenStream>(quote! {
+                impl #impl_gen #name #ty_gen #where_clause {
+                    /// Unwraps the inner value if it is a `T`, otherwise hands back `self`
+                    #[inline]
+                    pub fn extract<#param>(self) -> ::core::result::Result<#param, Self>
+                    where
+                        #param: #krate::VariantOf<Self>,
+                    {
+                        ::core::result::Result::map_err(
+                            <#param as #krate::VariantOf<Self>>::try_from_enum(self),
+                            #krate::UnwrapError::into_value,
+                        )
+                    }
+
+                    /// Borrows the inner value if it is a `T`
+                    #[inline]
+                    pub fn get<#param>(&self) -> ::core::option::Option<&#param>
+                    where
+                        #param: #krate::VariantOf<Self>,
+                    {
+                        <#param as #krate::VariantOf<Self>>::ref_from_enum(self)
+                    }
+
+                    /// Whether the inner value is a `T`
+                    #[inline]
+                    pub fn is<#param>(&self) -> ::core::primitive::bool
+                    where
+                        #param: #krate::VariantOf<Self>,
+                    {
+                        ::core::option::Option::is_some(
+                            &<#param as #krate::VariantOf<Self>>::ref_from_enum(self),
+                        )
+                    }
+                }
+            });
+        }
+
+        if !into_inners.is_empty() {
+            let has_others = variants.len() > 1;
+            needs_fallback |= has_others;
+            let methods = into_inners
+                .iter()
+                .map(|(var, ty, bound)| {
+                    let method = format_ident!("try_into_{}", snake_case(&var.ident.to_string()));
+                    let match_arm = match_arm(&name, var)?;
+                    let fallback_arm = has_others.then(|| fallback(ty));
+                    let bound = bound.as_ref().filter(|bound| !bound.is_empty());
+                    let where_clause = bound.map(|bound| quote!(where #(#bound),*));
+                    Ok(quote! {
+                        #[inline]
+                        pub fn #method(self) -> ::core::result::Result<#ty, #err_ty> #where_clause {
+                            match self {
+                                #match_arm
+                                #fallback_arm
+                            }
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>, GetFieldError>>()?;
+            unwraps.extend::<TokenStream>(quote! {
+                impl #impl_gen #name #ty_gen #where_clause {
+                    #(#methods)*
+                }
+            });
+        }
+
+        if !unwraps.is_empty() {
+            let helpers =
+                needs_fallback.then(|| derive_fallback(&name, &generics, &variants, traits));
+            stream.extend(quote! {
+                const _: () = {
+                    #helpers
+                    #unwraps
+                };
+            });
+        }
+        Ok(stream)
+    }
+}
+
+/// Derives a fieldless `kind` enum with a unit variant for every variant of `name`, along with
+/// `fn kind(&self)` and `From<&name>` to get it
+fn derive_kind(
+    name: &syn::Ident,
+    vis: &syn::Visibility,
+    generics: &syn::Generics,
+    variants: &[syn::Variant],
+    kind: &syn::Ident,
+    derives: &[syn::Path],
+) -> TokenStream {
+    let doc = format!("The variants of [`{name}`] without their fields");
+    let varnames: Vec<_> = variants.iter().map(|var| &var.ident).collect();
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    quote! {
+        #[doc = #doc]
+        #[derive(#(#derives),*)]
+        #vis enum #kind {
+            #(#varnames,)*
+        }
+
+        impl #impl_gen #name #ty_gen #where_clause {
+            #[inline]
+            pub fn kind(&self) -> #kind {
+                match self {
+                    #(Self::#varnames { .. } => #kind::#varnames,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_gen ::core::convert::From<&#name #ty_gen> for #kind #where_clause {
+            #[inline]
+            fn from(__value: &#name #ty_gen) -> Self {
+                <#name #ty_gen>::kind(__value)
+            }
+        }
+    }
+}
+
+/// Derives a `VARIANTS` table describing every variant of `name`, with `fn variant_name(&self)`
+fn derive_reflect(
+    name: &syn::Ident,
+    vis: &syn::Visibility,
+    generics: &syn::Generics,
+    variants: &[syn::Variant],
+) -> Result<TokenStream, Error> {
+    let info = format_ident!("{}VariantInfo", name);
+    let doc = format!("A variant of [`{name}`], as listed in `{name}::VARIANTS`");
+    let fieldless = is_fieldless(variants);
+    let entries = variants
+        .iter()
+        .map(|var| {
+            let attr =
+                VariantAttributes::load(&var.attrs).map_err(|(span, e)| Error::Special(span, e))?;
+            let varname = var.ident.to_string();
+            let inner = match get_field(&var.fields) {
+                Ok(field) => {
+                    let ty = field.ty.to_token_stream().to_string();
+                    quote!(::core::option::Option::Some(#ty))
+                }
+                Err(_) => quote!(::core::option::Option::None),
+            };
+            let single = !fieldless && get_field(&var.fields).is_ok();
+            let wrap = single && !attr.no_wrap;
+            let unwrap = single && !attr.no_unwrap && !attr.into_inner;
+            Ok(quote! {
+                #info {
+                    name: #varname,
+                    inner: #inner,
+                    wrap: #wrap,
+                    unwrap: #unwrap,
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let varnames = variants.iter().map(|var| &var.ident);
+    let varstrs = variants.iter().map(|var| var.ident.to_string());
+
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(
+            ::core::fmt::Debug,
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash
+        )]
+        #vis struct #info {
+            /// The name of the variant
+            pub name: &'static ::core::primitive::str,
+            /// The inner type as written, if the variant has a single field
+            pub inner: ::core::option::Option<&'static ::core::primitive::str>,
+            /// Whether `Wrap` derives `From` the inner type
+            pub wrap: ::core::primitive::bool,
+            /// Whether `Unwrap` derives `TryFrom` into the inner type
+            pub unwrap: ::core::primitive::bool,
+        }
+
+        impl #impl_gen #name #ty_gen #where_clause {
+            /// Every variant in declaration order
+            pub const VARIANTS: &'static [#info] = &[#(#entries),*];
+
+            /// The name of the variant
+            #[inline]
+            pub fn variant_name(&self) -> &'static ::core::primitive::str {
+                match self {
+                    #(Self::#varnames { .. } => #varstrs,)*
+                }
+            }
+        }
+    })
+}
+
+fn match_arm(name: &syn::Ident, var: &syn::Variant) -> Result<TokenStream, GetFieldError> {
+    let varname = &var.ident;
+    let field = get_field(&var.fields)?;
+    Ok(match field.ident {
+        Some(ref ident) => quote! {
+            #name::#varname{ #ident: __inner } => ::core::result::Result::Ok(__inner),
+        },
+        None => quote! {
+            #name::#varname(__inner) => ::core::result::Result::Ok(__inner),
+        },
+    })
+}
+
+/// The helpers shared by every `fallback_arm`: the qualified names of all variants, the index of
+/// a value's variant, and unless `traits` is set a macro building the error message of every
+/// variant for an inner type, so the expansion holds each variant name only once
+fn derive_fallback(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    variants: &[syn::Variant],
+    traits: Option<&syn::Path>,
+) -> TokenStream {
+    let varnames = variants.iter().map(|var| &var.ident);
+    let indices = 0..variants.len();
+    let qualified = variants
+        .iter()
+        .map(|var| format!("{}::{}", name, var.ident));
+    let len = variants.len();
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    let mut stream = quote! {
+        const __GIFTWRAP_VARIANTS: [&::core::primitive::str; #len] = [#(#qualified),*];
+
+        impl #impl_gen #name #ty_gen #where_clause {
+            #[inline]
+            fn __giftwrap_variant_index(&self) -> ::core::primitive::usize {
+                match self {
+                    #(Self::#varnames { .. } => #indices,)*
+                }
+            }
+        }
+    };
+    if traits.is_some() {
+        return stream;
+    }
+
+    // `concat!` builds the messages rather than a `const fn`, which is slow to evaluate for every
+    // variant of every inner type
+    let messages = variants
+        .iter()
+        .map(|var| format!("Can't convert {}::{} into ", name, var.ident));
+    stream.extend(quote! {
+        macro_rules! __giftwrap_messages {
+            ($ty:literal) => {
+                [#(::core::concat!(#messages, $ty)),*]
+            };
+        }
+    });
+    stream
+}
+
+/// The arm erroring for every variant that doesn't hold `ty`, through the helpers of
+/// `derive_fallback`
+fn fallback_arm(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    ty: &syn::Type,
+    traits: Option<&syn::Path>,
+) -> TokenStream {
+    let ty_str = ty.to_token_stream().to_string();
+    let (_, ty_gen, _) = generics.split_for_impl();
+    let variant = quote!(<#name #ty_gen>::__giftwrap_variant_index(&__value));
+    match traits {
+        Some(krate) => quote! {
+            __value => {
+                let __variant = __GIFTWRAP_VARIANTS[#variant];
+                ::core::result::Result::Err(#krate::UnwrapError::new(__value, __variant, #ty_str))
+            }
+        },
+        None => quote! {
+            __value => {
+                static __MESSAGES: [&::core::primitive::str; __GIFTWRAP_VARIANTS.len()] =
+                    __giftwrap_messages!(#ty_str);
+                ::core::result::Result::Err(__MESSAGES[#variant])
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::Derive, harled::FromDeriveInput, proc_macro2::TokenStream};
+
+    /// The number of tokens in the expansion of `Unwrap` for an enum of `variants` variants, each
+    /// holding a type of its own except for every tenth, which all share one
+    fn expansion_size(variants: usize, attrs: &str) -> usize {
+        fn count(stream: TokenStream) -> usize {
+            stream
+                .into_iter()
+                .map(|token| match token {
+                    proc_macro2::TokenTree::Group(g) => 1 + count(g.stream()),
+                    _ => 1,
+                })
+                .sum()
+        }
+
+        let variants: String = (0..variants)
+            .map(|i| format!("V{i}(Ty{}),", if i % 10 == 0 { 0 } else { i }))
+            .collect();
+        let input =
+            syn::parse_str(&format!("#[giftwrap({attrs})] enum Wide {{ {variants} }}")).unwrap();
+        count(Derive::from_derive_input(&input).unwrap().derive().unwrap())
+    }
+
+    #[test]
+    fn expansion_grows_linearly() {
+        for attrs in ["eq", "traits, eq"] {
+            let (small, large) = (expansion_size(100, attrs), expansion_size(400, attrs));
+            assert!(
+                large < small * 5,
+                "`{attrs}`: {small} tokens for 100 variants but {large} for 400"
+            );
+        }
+    }
+}
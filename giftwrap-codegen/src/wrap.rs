@@ -0,0 +1,627 @@
+use {
+    crate::{
+        attrib::{
+            repr_int, reprs, ContainerAttributes, StructAttributes, Toggle, VariantAttributes,
+        },
+        bounded, field_member, fresh_param, get_field,
+        identity::TypeIdentity,
+        is_fieldless,
+    },
+    coherence::{find_conflict, Conflict},
+    collection::derive_collection,
+    harled::FromDeriveInput,
+    helpers::{generate_inner_conversions, subtypes_list, trait_object, trim_self, Level},
+    ops::derive_ops,
+    proc_macro2::TokenStream,
+    quote::{format_ident, quote},
+    serialize::{derive_serde_enum, derive_serde_struct},
+    std::collections::HashSet,
+    syn::{self, parse_quote, spanned::Spanned, Type, TypeParamBound},
+};
+
+mod coherence;
+mod collection;
+mod helpers;
+mod ops;
+mod serialize;
+pub(crate) use helpers::Error;
+
+#[derive(FromDeriveInput, Debug)]
+pub(crate) enum Derive {
+    Struct(Struct),
+    Enum(Enum),
+}
+
+impl Derive {
+    pub(crate) fn derive(self) -> syn::Result<TokenStream> {
+        match self {
+            Self::Struct(s) => s.derive(),
+            Self::Enum(e) => e.derive(),
+        }
+        .map_err(syn::Error::from)
+    }
+}
+
+#[derive(FromDeriveInput, Debug)]
+#[harled(Struct)]
+pub(crate) struct Struct {
+    ident: syn::Ident,
+    attrs: Vec<syn::Attribute>,
+    generics: syn::Generics,
+    fields: syn::Fields,
+}
+
+impl Struct {
+    fn derive(self) -> Result<TokenStream, Error> {
+        let Self {
+            ident,
+            attrs,
+            generics,
+            fields,
+        } = self;
+
+        let mut stream = TokenStream::new();
+
+        let container =
+            ContainerAttributes::load(&attrs).map_err(|(span, e)| Error::Special(span, e))?;
+        let bound = container
+            .bound()
+            .map_err(|e| Error::Special(ident.span(), e))?;
+        let infer = bound.is_none();
+        let generics = bounded(&generics, bound.into_iter().flatten());
+        let field = get_field(&fields)?;
+        let attr =
+            StructAttributes::load(&field.attrs).map_err(|(span, e)| Error::Special(span, e))?;
+        let levels = trim_self(
+            subtypes_list(&field.ty, attr.wrap_depth())?,
+            attr.wrap_depth(),
+            &ident,
+            &generics,
+        )?;
+
+        let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+        let self_ty: Type = parse_quote!(#ident #ty_gen);
+        let wraps: Vec<_> = levels.iter().map(|level| (0, &level.ty)).collect();
+        if let Some(conflict) = find_conflict(&self_ty, &generics, &wraps) {
+            return Err(Error::Special(
+                fields.span(),
+                match conflict {
+                    Conflict::Reflexive(_) => concat!(
+                        "Wrapping a generic type will conflict with `impl<T> From<T> for T` in core\n",
+                        "\tConsider using `wrapDepth` here"
+                    ),
+                    _ => concat!(
+                        "Generic type cannot be wrapped without causing conflicting implementations\n",
+                        "\tConsider using `wrapDepth` here"
+                    ),
+                },
+            ));
+        }
+
+        for level in &levels {
+            let Level { ty, through, .. } = level;
+            let froms = generate_inner_conversions(through);
+            let from_ty = match &field.ident {
+                Some(ident) => quote! {
+                    Self{ #ident: #froms }
+                },
+                None => quote! {
+                    Self(#froms)
+                },
+            };
+            stream.extend(level.scope(quote! {
+                #[automatically_derived]
+                impl #impl_gen ::core::convert::From<#ty> for #ident #ty_gen #where_clause {
+                    #[inline]
+                    fn from(__value: #ty) -> Self {
+                        #from_ty
+                    }
+                }
+            }));
+        }
+
+        if let Some(new) = Toggle::ident(&container.const_new, "new")
+            .map_err(|e| Error::Special(ident.span(), e))?
+        {
+            let vis = container
+                .const_vis()
+                .map_err(|e| Error::Special(ident.span(), e))?;
+            let ty = &field.ty;
+            let from_inner = match &field.ident {
+                Some(field) => quote!(Self { #field: __inner }),
+                None => quote!(Self(__inner)),
+            };
+            stream.extend(quote! {
+                impl #impl_gen #ident #ty_gen #where_clause {
+                    #[inline]
+                    #vis const fn #new(__inner: #ty) -> Self {
+                        #from_inner
+                    }
+                }
+            });
+        }
+
+        stream.extend(derive_ops(&container.ops, &ident, &generics, field, infer));
+        if container.collection {
+            stream.extend(derive_collection(&ident, &generics, field, infer)?);
+        }
+
+        if container.ref_cast {
+            if !reprs(&attrs).iter().any(|repr| repr == "transparent") {
+                return Err(Error::Special(
+                    ident.span(),
+                    concat!(
+                        "`refCast` needs `#[repr(transparent)]` to guarantee the struct has the layout of its field\n",
+                        "\tConsider using `#[repr(transparent)]` here"
+                    ),
+                ));
+            }
+            stream.extend(derive_ref_cast(&ident, &generics, &field.ty));
+        }
+
+        if container.traits {
+            let krate = container
+                .krate()
+                .map_err(|e| Error::Special(ident.span(), e))?;
+            let ty = &field.ty;
+            let member = field_member(field);
+            let from_inner = match &field.ident {
+                Some(ident) => quote!(Self { #ident: __inner }),
+                None => quote!(Self(__inner)),
+            };
+            stream.extend(quote! {
+                #[automatically_derived]
+                impl #impl_gen #krate::Wrapper for #ident #ty_gen #where_clause {
+                    type Inner = #ty;
+
+                    #[inline]
+                    fn from_inner(__inner: #ty) -> Self {
+                        #from_inner
+                    }
+
+                    #[inline]
+                    fn into_inner(self) -> #ty {
+                        self.#member
+                    }
+                }
+            });
+        }
+
+        if container.serde {
+            stream.extend(derive_serde_struct(&ident, &generics, field, infer));
+        }
+        Ok(stream)
+    }
+}
+
+/// Derives casts from references and slices of `ty` to `ident`, which must be
+/// `#[repr(transparent)]`. The layout is checked once more when the casts are instantiated.
+fn derive_ref_cast(ident: &syn::Ident, generics: &syn::Generics, ty: &Type) -> TokenStream {
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_gen #ident #ty_gen #where_clause {
+            #[doc(hidden)]
+            const __GIFTWRAP_SAME_LAYOUT: () = ::core::assert!(
+                ::core::mem::size_of::<#ty>() == ::core::mem::size_of::<Self>()
+                    && ::core::mem::align_of::<#ty>() == ::core::mem::align_of::<Self>()
+            );
+
+            /// Views a reference to the inner value as a reference to `Self`
+            #[inline]
+            pub fn from_ref(__inner: &#ty) -> &Self {
+                let () = Self::__GIFTWRAP_SAME_LAYOUT;
+                unsafe { &*(__inner as *const #ty as *const Self) }
+            }
+
+            /// Views a mutable reference to the inner value as a mutable reference to `Self`
+            #[inline]
+            pub fn from_mut(__inner: &mut #ty) -> &mut Self {
+                let () = Self::__GIFTWRAP_SAME_LAYOUT;
+                unsafe { &mut *(__inner as *mut #ty as *mut Self) }
+            }
+
+            /// Views a slice of inner values as a slice of `Self`
+            #[inline]
+            pub fn from_slice(__inner: &[#ty]) -> &[Self] {
+                let () = Self::__GIFTWRAP_SAME_LAYOUT;
+                unsafe { &*(__inner as *const [#ty] as *const [Self]) }
+            }
+
+            /// Views a mutable slice of inner values as a mutable slice of `Self`
+            #[inline]
+            pub fn from_mut_slice(__inner: &mut [#ty]) -> &mut [Self] {
+                let () = Self::__GIFTWRAP_SAME_LAYOUT;
+                unsafe { &mut *(__inner as *mut [#ty] as *mut [Self]) }
+            }
+        }
+    }
+}
+
+#[derive(FromDeriveInput, Debug)]
+#[harled(Enum)]
+pub(crate) struct Enum {
+    ident: syn::Ident,
+    vis: syn::Visibility,
+    attrs: Vec<syn::Attribute>,
+    generics: syn::Generics,
+    variants: Vec<syn::Variant>,
+}
+
+impl Enum {
+    fn derive(mut self) -> Result<TokenStream, Error> {
+        let container =
+            ContainerAttributes::load(&self.attrs).map_err(|(span, e)| Error::Special(span, e))?;
+        let bound = container
+            .bound()
+            .map_err(|e| Error::Special(self.ident.span(), e))?;
+        self.generics = bounded(&self.generics, bound.into_iter().flatten());
+        if is_fieldless(&self.variants) {
+            if container.serde {
+                return Err(Error::Special(
+                    self.ident.span(),
+                    "`serde` can only be used on structs and enums with fields",
+                ));
+            }
+            return self.derive_fieldless(container);
+        }
+        if container.strings {
+            return Err(Error::Special(
+                self.ident.span(),
+                "`strings` can only be used on fieldless enums",
+            ));
+        }
+        if container.ref_cast {
+            return Err(Error::Special(
+                self.ident.span(),
+                "`refCast` can only be used on structs",
+            ));
+        }
+        if !container.ops.is_empty() {
+            return Err(Error::Special(
+                self.ident.span(),
+                "`ops` can only be used on structs",
+            ));
+        }
+        if container.collection {
+            return Err(Error::Special(
+                self.ident.span(),
+                "`collection` can only be used on structs",
+            ));
+        }
+
+        let Self {
+            ident,
+            generics,
+            variants,
+            ..
+        } = self;
+
+        let mut stream = TokenStream::new();
+
+        let variant_bound = |var: &syn::Variant, attr: &VariantAttributes| {
+            attr.bound(&container)
+                .map_err(|e| Error::Special(var.span(), e))
+        };
+
+        let serialized = match container.serde {
+            true => variants
+                .iter()
+                .map(|var| {
+                    let attr = VariantAttributes::load(&var.attrs)
+                        .map_err(|(span, e)| Error::Special(span, e))?;
+                    Ok((var, get_field(&var.fields)?, variant_bound(var, &attr)?))
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+            false => vec![],
+        };
+
+        let mut wrapped = vec![];
+        let mut any_impl = None;
+        for res in variants
+            .iter()
+            .filter_map(|var| match VariantAttributes::load(&var.attrs) {
+                Ok(attr) => (!attr.no_wrap).then_some(Ok((var, attr))),
+                Err((span, e)) => Some(Err(Error::Special(span, e))),
+            })
+        {
+            let (var, attr) = res?;
+            let field = get_field(&var.fields)?;
+            let bound = variant_bound(var, &attr)?;
+
+            if attr.any_impl {
+                if any_impl.is_some() {
+                    return Err(Error::Special(
+                        var.span(),
+                        concat!(
+                            "Cannot derive Wrap for two variants with `anyImpl`\n",
+                            "\tConsider using `noWrap` here"
+                        ),
+                    ));
+                }
+                let (ptr, bounds) = trait_object(&field.ty).ok_or(Error::Special(
+                    field.ty.span(),
+                    "`anyImpl` can only be used on a variant holding a `Box`, `Rc` or `Arc` of `dyn Trait`",
+                ))?;
+                any_impl = Some((var, field, ptr, bounds, bound));
+                continue;
+            }
+
+            let levels = trim_self(
+                subtypes_list(&field.ty, attr.wrap_depth())?,
+                attr.wrap_depth(),
+                &ident,
+                &generics,
+            )?;
+            wrapped.push((var, field, levels, bound));
+        }
+
+        if let (Some(_), Some((var, ..))) = (&any_impl, wrapped.first()) {
+            return Err(Error::Special(
+                var.span(),
+                concat!(
+                    "Cannot derive Wrap for any other variant alongside `anyImpl`, its blanket `From` impl overlaps with every other `From` whatever the inner type\n",
+                    "\tConsider using `noWrap` here"
+                ),
+            ));
+        }
+
+        let (_, ty_gen, _) = generics.split_for_impl();
+        let self_ty: Type = parse_quote!(#ident #ty_gen);
+        // Conflicts are found between the types as normalized, `String` is `std::string::String`
+        let identity = &TypeIdentity::new(&generics, &variants)
+            .map_err(|(span, e)| Error::Special(span, e))?;
+        let normalized: Vec<_> = wrapped
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, _, levels, _))| {
+                levels
+                    .iter()
+                    .map(move |level| (i, identity.normalize(&level.ty)))
+            })
+            .collect();
+        let wraps: Vec<_> = normalized.iter().map(|(i, ty)| (*i, ty)).collect();
+        if let Some(conflict) = find_conflict(&self_ty, &generics, &wraps) {
+            return Err(match conflict {
+                Conflict::Reflexive(i) => Error::Special(
+                    wrapped[i].0.fields.span(),
+                    concat!(
+                        "Wrapping a generic type will conflict with `impl<T> From<T> for T` in core\n",
+                        "\tConsider using `noWrap` or `wrapDepth` here"
+                    ),
+                ),
+                Conflict::Generic(i) => Error::Special(
+                    wrapped[i].0.fields.span(),
+                    concat!(
+                        "Wrapping a generic type will cause conflicting implementations\n",
+                        "\tConsider using `noWrap` or `wrapDepth` here"
+                    ),
+                ),
+                Conflict::Duplicate(i) => Error::Special(
+                    wrapped[i].0.span(),
+                    concat!(
+                        "Cannot derive Wrap for two variants with the same inner type\n",
+                        "\tConsider using `noWrap` or `wrapDepth` here"
+                    ),
+                ),
+            });
+        }
+
+        for (var, field, levels, bound) in &wrapped {
+            let var_generics = bounded(&generics, bound.iter().flatten().cloned());
+            let (impl_gen, _, where_clause) = var_generics.split_for_impl();
+            for level in levels {
+                let Level { ty, through, .. } = level;
+                let froms = generate_inner_conversions(through);
+
+                let varname = &var.ident;
+                let from_ty = match &field.ident {
+                    Some(ident) => quote! {
+                        Self::#varname{ #ident: #froms }
+                    },
+                    None => quote! {
+                        Self::#varname(#froms)
+                    },
+                };
+
+                stream.extend(level.scope(quote! {
+                    #[automatically_derived]
+                    impl #impl_gen ::core::convert::From<#ty> for #ident #ty_gen #where_clause {
+                        #[inline]
+                        fn from(__value: #ty) -> Self {
+                            #from_ty
+                        }
+                    }
+                }));
+            }
+        }
+
+        if let Some((var, field, ptr, mut bounds, bound)) = any_impl {
+            if !bounds
+                .iter()
+                .any(|bound| matches!(bound, TypeParamBound::Lifetime(_)))
+            {
+                bounds.push(parse_quote!('static));
+            }
+            let param = fresh_param(&generics, "__E");
+            let mut any_generics = bounded(&generics, bound.into_iter().flatten());
+            any_generics.params.push(parse_quote!(#param: #bounds));
+            let (any_impl_gen, _, any_where_clause) = any_generics.split_for_impl();
+
+            let varname = &var.ident;
+            let from_ty = match &field.ident {
+                Some(ident) => quote! {
+                    Self::#varname{ #ident: #ptr::new(__value) }
+                },
+                None => quote! {
+                    Self::#varname(#ptr::new(__value))
+                },
+            };
+
+            stream.extend::<TokenStream>(quote! {
+                #[automatically_derived]
+                impl #any_impl_gen ::core::convert::From<#param> for #ident #ty_gen #any_where_clause {
+                    #[inline]
+                    fn from(__value: #param) -> Self {
+                        #from_ty
+                    }
+                }
+            });
+        }
+
+        if container.serde {
+            let deserialized: Vec<_> = wrapped
+                .iter()
+                .map(|(var, field, _, bound)| (*var, *field, bound.clone()))
+                .collect();
+            stream.extend(derive_serde_enum(
+                &ident,
+                &generics,
+                &serialized,
+                &deserialized,
+            ));
+        }
+
+        Ok(stream)
+    }
+
+    /// Derives `TryFrom` the discriminant's integer type, with an error holding any invalid value,
+    /// and with `strings` also `TryFrom<&str>` and `FromStr` by variant name
+    fn derive_fieldless(self, container: ContainerAttributes) -> Result<TokenStream, Error> {
+        let Self {
+            ident,
+            vis,
+            attrs,
+            generics,
+            variants,
+        } = self;
+
+        let repr = repr_int(&attrs);
+        let repr = quote!(::core::primitive::#repr);
+        let err_ident = format_ident!("{}DiscriminantError", ident);
+        let err_doc = format!("A value that is not a discriminant of [`{ident}`]");
+        let err_msg = format!("{{}} is not a discriminant of {ident}");
+        let varnames = variants.iter().map(|var| &var.ident);
+        let derives = quote! {
+            ::core::fmt::Debug,
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash
+        };
+
+        let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+        let mut stream = quote! {
+            #[doc = #err_doc]
+            #[derive(#derives)]
+            #vis struct #err_ident(pub #repr);
+
+            #[automatically_derived]
+            impl ::core::fmt::Display for #err_ident {
+                fn fmt(&self, __formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::write!(__formatter, #err_msg, self.0)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::core::error::Error for #err_ident {}
+
+            #[automatically_derived]
+            impl #impl_gen ::core::convert::TryFrom<#repr> for #ident #ty_gen #where_clause {
+                type Error = #err_ident;
+
+                #[inline]
+                fn try_from(__value: #repr) -> ::core::result::Result<Self, #err_ident> {
+                    match __value {
+                        #(__value if __value == Self::#varnames as #repr => {
+                            ::core::result::Result::Ok(Self::#varnames)
+                        })*
+                        __value => ::core::result::Result::Err(#err_ident(__value)),
+                    }
+                }
+            }
+        };
+        if !container.strings {
+            return Ok(stream);
+        }
+
+        let mut seen = HashSet::new();
+        let mut names = vec![];
+        let mut arms = vec![];
+        for var in &variants {
+            let attr =
+                VariantAttributes::load(&var.attrs).map_err(|(span, e)| Error::Special(span, e))?;
+            let name = attr.name(&var.ident, container.rename_all);
+            let accepted: Vec<_> = std::iter::once(&name).chain(&attr.alias).collect();
+            if !accepted.iter().all(|name| seen.insert(name.to_string())) {
+                return Err(Error::Special(
+                    var.span(),
+                    concat!(
+                        "Cannot derive Wrap for two variants with the same name\n",
+                        "\tConsider using `name` or `alias` here"
+                    ),
+                ));
+            }
+            let varname = &var.ident;
+            arms.push(quote! {
+                #(#accepted)|* => ::core::result::Result::Ok(Self::#varname),
+            });
+            names.push(name);
+        }
+
+        let name_err = format_ident!("{}NameError", ident);
+        let name_err_doc = format!("A string that is not the name of a variant of [`{ident}`]");
+        let name_err_msg = format!(
+            "not a variant of {ident}, expected one of {}",
+            names
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        stream.extend(quote! {
+            #[doc = #name_err_doc]
+            #[derive(#derives)]
+            #vis struct #name_err;
+
+            impl #name_err {
+                /// The names of all variants, without aliases
+                pub const NAMES: &'static [&'static ::core::primitive::str] = &[#(#names),*];
+            }
+
+            #[automatically_derived]
+            impl ::core::fmt::Display for #name_err {
+                fn fmt(&self, __formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, #name_err_msg)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::core::error::Error for #name_err {}
+
+            #[automatically_derived]
+            impl #impl_gen ::core::convert::TryFrom<&::core::primitive::str> for #ident #ty_gen #where_clause {
+                type Error = #name_err;
+
+                #[inline]
+                fn try_from(__value: &::core::primitive::str) -> ::core::result::Result<Self, #name_err> {
+                    match __value {
+                        #(#arms)*
+                        _ => ::core::result::Result::Err(#name_err),
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_gen ::core::str::FromStr for #ident #ty_gen #where_clause {
+                type Err = #name_err;
+
+                #[inline]
+                fn from_str(__value: &::core::primitive::str) -> ::core::result::Result<Self, #name_err> {
+                    <Self as ::core::convert::TryFrom<&::core::primitive::str>>::try_from(__value)
+                }
+            }
+        });
+        Ok(stream)
+    }
+}
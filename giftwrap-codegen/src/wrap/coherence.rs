@@ -0,0 +1,221 @@
+use {
+    quote::ToTokens,
+    std::collections::HashMap,
+    syn::{self, GenericArgument, Generics, PathArguments, Type},
+};
+
+/// Nesting depth after which two types are assumed to overlap, guards against cyclic bindings
+const MAX_DEPTH: usize = 64;
+
+/// A conflict found between the `From` impls of a derive, holding the index of the offending wrap
+pub(super) enum Conflict {
+    /// `From<X>` could overlap with `impl<T> From<T> for T`
+    Reflexive(usize),
+    /// `From<X>` could overlap with another `From<Y>` where either is generic
+    Generic(usize),
+    /// `From<X>` is derived twice for the exact same type
+    Duplicate(usize),
+}
+
+/// Finds any pair of `From<X> for #self_ty` impls that rustc would consider overlapping, including
+/// the blanket `impl<T> From<T> for T`, regardless of the order they are declared in.
+///
+/// `wraps` pairs every type `From` is derived for with the index of the variant (or field) it
+/// wraps, the index is passed back in the [`Conflict`].
+pub(super) fn find_conflict(
+    self_ty: &Type,
+    generics: &Generics,
+    wraps: &[(usize, &Type)],
+) -> Option<Conflict> {
+    let self_term = Term::new(self_ty, generics);
+    let terms: Vec<_> = wraps
+        .iter()
+        .map(|(i, ty)| (*i, Term::new(ty, generics).replace_self(&self_term)))
+        .collect();
+
+    if let Some((i, _)) = terms
+        .iter()
+        .find(|(_, term)| Unifier::default().unify(term, &self_term, 0))
+    {
+        return Some(Conflict::Reflexive(*i));
+    }
+
+    for (n, (i, a)) in terms.iter().enumerate() {
+        for (j, b) in &terms[n + 1..] {
+            // Both impls are for the same `Self`, so they share its type parameters
+            if Unifier::default().unify(a, b, 0) {
+                return Some(match (a.is_generic(), b.is_generic()) {
+                    (true, _) => Conflict::Generic(*i),
+                    (false, true) => Conflict::Generic(*j),
+                    (false, false) => Conflict::Duplicate(*j),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// A syntactic type reduced to what coherence cares about, lifetimes are erased
+#[derive(Clone, Debug)]
+enum Term {
+    /// One of the type parameters of the derived type, or a projection on one
+    Param(String),
+    /// A type constructor applied to its type arguments, opaque types have no arguments
+    App(String, Vec<Term>),
+}
+
+impl Term {
+    fn new(ty: &Type, generics: &Generics) -> Self {
+        let opaque = |ty: &dyn ToTokens| Term::App(ty.to_token_stream().to_string(), vec![]);
+        match ty {
+            // Projections such as `<T as Trait>::Assoc` could normalize to any type
+            Type::Path(p) if p.qself.is_some() => Term::Param(ty.to_token_stream().to_string()),
+            Type::Path(p) => {
+                if let Some(ident) = p.path.get_ident() {
+                    if generics.type_params().any(|param| &param.ident == ident) {
+                        return Term::Param(ident.to_string());
+                    }
+                }
+                // `T::Assoc` is not the parameter itself but is just as unknown
+                if generics
+                    .type_params()
+                    .any(|param| param.ident == p.path.segments[0].ident)
+                {
+                    return Term::Param(ty.to_token_stream().to_string());
+                }
+
+                let head = p
+                    .path
+                    .segments
+                    .iter()
+                    .map(|seg| seg.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                let args = p
+                    .path
+                    .segments
+                    .iter()
+                    .filter_map(|seg| match &seg.arguments {
+                        PathArguments::AngleBracketed(brac) => Some(&brac.args),
+                        _ => None,
+                    })
+                    .flatten()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(Term::new(ty, generics)),
+                        GenericArgument::Const(c) => Some(opaque(c)),
+                        _ => None,
+                    })
+                    .collect();
+                Term::App(head, args)
+            }
+            Type::Reference(r) => Term::App(
+                if r.mutability.is_some() { "&mut" } else { "&" }.into(),
+                vec![Term::new(&r.elem, generics)],
+            ),
+            Type::Ptr(p) => Term::App(
+                if p.mutability.is_some() {
+                    "*mut"
+                } else {
+                    "*const"
+                }
+                .into(),
+                vec![Term::new(&p.elem, generics)],
+            ),
+            Type::Slice(s) => Term::App("[]".into(), vec![Term::new(&s.elem, generics)]),
+            Type::Array(a) => Term::App(
+                format!("[; {}]", a.len.to_token_stream()),
+                vec![Term::new(&a.elem, generics)],
+            ),
+            Type::Tuple(t) => Term::App(
+                "()".into(),
+                t.elems.iter().map(|ty| Term::new(ty, generics)).collect(),
+            ),
+            Type::Paren(p) => Term::new(&p.elem, generics),
+            Type::Group(g) => Term::new(&g.elem, generics),
+            _ => opaque(ty),
+        }
+    }
+
+    /// Replaces any `Self` with the derived type
+    fn replace_self(self, self_term: &Term) -> Self {
+        match self {
+            Term::App(head, args) if head == "Self" && args.is_empty() => self_term.clone(),
+            Term::App(head, args) => Term::App(
+                head,
+                args.into_iter()
+                    .map(|arg| arg.replace_self(self_term))
+                    .collect(),
+            ),
+            param => param,
+        }
+    }
+
+    fn is_generic(&self) -> bool {
+        match self {
+            Term::Param(_) => true,
+            Term::App(_, args) => args.iter().any(Term::is_generic),
+        }
+    }
+}
+
+/// Unification of two terms over the type parameters of the derived type, which every impl shares
+#[derive(Default)]
+struct Unifier {
+    bindings: HashMap<String, Term>,
+}
+
+impl Unifier {
+    fn resolve<'t>(&'t self, mut term: &'t Term) -> &'t Term {
+        for _ in 0..MAX_DEPTH {
+            match term {
+                Term::Param(name) => match self.bindings.get(name) {
+                    Some(bound) => term = bound,
+                    None => break,
+                },
+                Term::App(..) => break,
+            }
+        }
+        term
+    }
+
+    /// Binds `param` to `term` unless `term` contains it, `T` and `Wrapper<T>` never unify
+    fn bind(&mut self, param: &str, term: Term, depth: usize) -> bool {
+        if self.occurs(param, &term, depth) {
+            return false;
+        }
+        self.bindings.insert(param.to_string(), term);
+        true
+    }
+
+    fn occurs(&self, param: &str, term: &Term, depth: usize) -> bool {
+        if depth > MAX_DEPTH {
+            return false;
+        }
+        match self.resolve(term) {
+            Term::Param(name) => name == param,
+            Term::App(_, args) => args.iter().any(|arg| self.occurs(param, arg, depth + 1)),
+        }
+    }
+
+    fn unify(&mut self, a: &Term, b: &Term, depth: usize) -> bool {
+        if depth > MAX_DEPTH {
+            return true;
+        }
+
+        let (a, b) = (self.resolve(a).clone(), self.resolve(b).clone());
+        match (&a, &b) {
+            (Term::Param(x), Term::Param(y)) if x == y => true,
+            (Term::Param(x), _) => self.bind(x, b, depth),
+            (_, Term::Param(y)) => self.bind(y, a, depth),
+            (Term::App(a_head, a_args), Term::App(b_head, b_args)) => {
+                a_head == b_head
+                    && a_args.len() == b_args.len()
+                    && a_args
+                        .iter()
+                        .zip(b_args)
+                        .all(|(a, b)| self.unify(a, b, depth + 1))
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use {
+    super::{helpers::type_args, Error},
+    crate::{bounded, field_member, fresh_lifetime, fresh_param},
+    proc_macro2::TokenStream,
+    quote::quote,
+    syn::{self, parse_quote, spanned::Spanned, Type},
+};
+
+/// Derives `FromIterator`, `Extend`, `IntoIterator` for owned, `&` and `&mut` receivers, and
+/// `Index`/`IndexMut` for a struct whose single field is a collection, all delegating to the field
+/// and bounded on the field implementing the same trait if `infer` is set
+pub(super) fn derive_collection(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field: &syn::Field,
+    infer: bool,
+) -> Result<TokenStream, Error> {
+    let ty = &field.ty;
+    let elem = element(ty).ok_or(Error::Special(
+        ty.span(),
+        "`collection` can only be used on a field with a collection type such as `Vec<T>` or `HashMap<K, V>`",
+    ))?;
+    let member = field_member(field);
+    let from_inner = match &field.ident {
+        Some(ident) => quote!(Self { #ident: __inner }),
+        None => quote!(Self(__inner)),
+    };
+    let (_, ty_gen, _) = generics.split_for_impl();
+    // Bounds on the parameters introduced by an impl itself can't be written through `bound`, so
+    // those are always kept
+    let inferred = |predicate: syn::WherePredicate| bounded(generics, infer.then_some(predicate));
+
+    let mut stream = TokenStream::new();
+
+    let iter = fresh_param(generics, "__I");
+    let from_iter_generics = inferred(parse_quote!(#ty: ::core::iter::FromIterator<#elem>));
+    let (impl_gen, _, where_clause) = from_iter_generics.split_for_impl();
+    stream.extend(quote! {
+        #[automatically_derived]
+        impl #impl_gen ::core::iter::FromIterator<#elem> for #ident #ty_gen #where_clause {
+            #[inline]
+            fn from_iter<#iter>(__iter: #iter) -> Self
+            where
+                #iter: ::core::iter::IntoIterator<Item = #elem>,
+            {
+                let __inner = <#ty as ::core::iter::FromIterator<#elem>>::from_iter(__iter);
+                #from_inner
+            }
+        }
+    });
+
+    let extend_generics = inferred(parse_quote!(#ty: ::core::iter::Extend<#elem>));
+    let (impl_gen, _, where_clause) = extend_generics.split_for_impl();
+    stream.extend(quote! {
+        #[automatically_derived]
+        impl #impl_gen ::core::iter::Extend<#elem> for #ident #ty_gen #where_clause {
+            #[inline]
+            fn extend<#iter>(&mut self, __iter: #iter)
+            where
+                #iter: ::core::iter::IntoIterator<Item = #elem>,
+            {
+                ::core::iter::Extend::extend(&mut self.#member, __iter)
+            }
+        }
+    });
+
+    let into_iter_generics = inferred(parse_quote!(#ty: ::core::iter::IntoIterator));
+    let (impl_gen, _, where_clause) = into_iter_generics.split_for_impl();
+    stream.extend(quote! {
+        #[automatically_derived]
+        impl #impl_gen ::core::iter::IntoIterator for #ident #ty_gen #where_clause {
+            type Item = <#ty as ::core::iter::IntoIterator>::Item;
+            type IntoIter = <#ty as ::core::iter::IntoIterator>::IntoIter;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                ::core::iter::IntoIterator::into_iter(self.#member)
+            }
+        }
+    });
+
+    let lt = fresh_lifetime(generics, "a");
+    for (mutability, receiver) in [
+        (None, quote!(&self.#member)),
+        (Some(quote!(mut)), quote!(&mut self.#member)),
+    ] {
+        let mut ref_generics = bounded(
+            generics,
+            [parse_quote!(&#lt #mutability #ty: ::core::iter::IntoIterator)],
+        );
+        ref_generics.params.insert(0, parse_quote!(#lt));
+        let (impl_gen, _, where_clause) = ref_generics.split_for_impl();
+        stream.extend(quote! {
+            #[automatically_derived]
+            impl #impl_gen ::core::iter::IntoIterator for &#lt #mutability #ident #ty_gen #where_clause {
+                type Item = <&#lt #mutability #ty as ::core::iter::IntoIterator>::Item;
+                type IntoIter = <&#lt #mutability #ty as ::core::iter::IntoIterator>::IntoIter;
+
+                #[inline]
+                fn into_iter(self) -> Self::IntoIter {
+                    ::core::iter::IntoIterator::into_iter(#receiver)
+                }
+            }
+        });
+    }
+
+    let idx = fresh_param(generics, "__Idx");
+    let mut index_generics = bounded(generics, [parse_quote!(#ty: ::core::ops::Index<#idx>)]);
+    index_generics.params.push(parse_quote!(#idx));
+    let (impl_gen, _, where_clause) = index_generics.split_for_impl();
+    stream.extend(quote! {
+        #[automatically_derived]
+        impl #impl_gen ::core::ops::Index<#idx> for #ident #ty_gen #where_clause {
+            type Output = <#ty as ::core::ops::Index<#idx>>::Output;
+
+            #[inline]
+            fn index(&self, __index: #idx) -> &Self::Output {
+                ::core::ops::Index::index(&self.#member, __index)
+            }
+        }
+    });
+
+    let mut index_mut_generics =
+        bounded(generics, [parse_quote!(#ty: ::core::ops::IndexMut<#idx>)]);
+    index_mut_generics.params.push(parse_quote!(#idx));
+    let (impl_gen, _, where_clause) = index_mut_generics.split_for_impl();
+    stream.extend(quote! {
+        #[automatically_derived]
+        impl #impl_gen ::core::ops::IndexMut<#idx> for #ident #ty_gen #where_clause {
+            #[inline]
+            fn index_mut(&mut self, __index: #idx) -> &mut Self::Output {
+                ::core::ops::IndexMut::index_mut(&mut self.#member, __index)
+            }
+        }
+    });
+
+    Ok(stream)
+}
+
+/// The element type of a collection, `(K, V)` for maps such as `HashMap<K, V>` and the first type
+/// argument otherwise
+fn element(ty: &Type) -> Option<TokenStream> {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() => p,
+        Type::Paren(p) => return element(&p.elem),
+        Type::Group(g) => return element(&g.elem),
+        _ => return None,
+    };
+    let args = type_args(path);
+    let is_map = path
+        .path
+        .segments
+        .last()?
+        .ident
+        .to_string()
+        .ends_with("Map");
+    match args.as_slice() {
+        [key, value, ..] if is_map => Some(quote!((#key, #value))),
+        [elem, ..] => Some(quote!(#elem)),
+        [] => None,
+    }
+}
@@ -0,0 +1,334 @@
+use {
+    crate::GetFieldError,
+    proc_macro2::{Span, TokenStream},
+    quote::quote,
+    syn::{
+        self, parse_quote, punctuated::Punctuated, spanned::Spanned, GenericArgument, Generics,
+        Ident, PathArguments, Token, Type, TypeParamBound,
+    },
+};
+
+pub(crate) enum Error {
+    For(Span, &'static str),
+    Only(Span, &'static str),
+    Special(Span, &'static str),
+}
+
+impl From<Error> for syn::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::For(span, msg) => {
+                syn::Error::new(span, format!("Wrap cannot be derived for {msg}"))
+            }
+            Error::Only(span, msg) => {
+                syn::Error::new(span, format!("Wrap can only be derived for {msg}"))
+            }
+            Error::Special(span, msg) => syn::Error::new(span, msg),
+        }
+    }
+}
+
+impl From<GetFieldError> for Error {
+    fn from(e: GetFieldError) -> Self {
+        match e {
+            GetFieldError::Unit(span) => Error::For(span, "Unit variant"),
+            GetFieldError::NotSingle(span) => Error::Only(span, "variant with 1 field"),
+        }
+    }
+}
+
+/// A type `From` is derived for
+pub(super) struct Level {
+    pub ty: Type,
+    /// The outer levels it is converted through, outermost first
+    pub through: Vec<Type>,
+    /// Whether `ty` is a type of `alloc` named through [`ALLOC`], such as the `String` of `Arc<str>`
+    pub alloc: bool,
+}
+
+/// The name `alloc` types are named through, brought into scope by [`Level::scope`] since `::alloc`
+/// only resolves in crates declaring `extern crate alloc`
+const ALLOC: &str = "__giftwrap_alloc";
+
+impl Level {
+    /// Scopes `item` in an anonymous const with `alloc` in scope if needed
+    pub(super) fn scope(&self, item: TokenStream) -> TokenStream {
+        if !self.alloc {
+            return item;
+        }
+        let alloc = Ident::new(ALLOC, Span::call_site());
+        quote! {
+            const _: () = {
+                extern crate alloc as #alloc;
+                #item
+            };
+        }
+    }
+}
+
+pub(super) fn generate_inner_conversions(types: &[Type]) -> TokenStream {
+    types.iter().rev().fold(quote! {__value}, |froms, s_ty| {
+        quote! {
+            <#s_ty as ::core::convert::From<_>>::from(#froms)
+        }
+    })
+}
+
+/// Walks the chain of inner types of `top`, `depth` levels down or as far as possible if `None`.
+///
+/// References and pointers end the chain since they can't be built from an owned value. Unsized
+/// types such as `str`, `[T]` and `dyn Trait` end it as well, but for pointers with a known sized
+/// source, like `Arc<str>` from `String` and `&str`, those sources take the place of the unsized
+/// level. Explicitly asking for a level past the end through `wrapDepth` is an error.
+pub(super) fn subtypes_list(top: &syn::Type, depth: Option<u32>) -> Result<Vec<Level>, Error> {
+    let mut vec = vec![];
+    let mut through = vec![];
+
+    let mut current = top;
+    let unreachable = loop {
+        if depth.is_some_and(|d| through.len() == d as usize) {
+            return Ok(vec);
+        }
+        match current {
+            Type::Paren(p) => {
+                current = &*p.elem;
+                continue;
+            }
+            Type::Group(g) => {
+                current = &*g.elem;
+                continue;
+            }
+            _ => {}
+        }
+        vec.push(Level {
+            ty: current.clone(),
+            through: through.clone(),
+            alloc: false,
+        });
+
+        let next_ty = match current {
+            Type::Path(path) => type_args(path).into_iter().next(),
+            Type::Ptr(ptr) => Some(&*ptr.elem),
+            Type::Reference(reference) => Some(&*reference.elem),
+            _ => None,
+        };
+        let next_ty = match next_ty {
+            Some(ty) => ty,
+            None => {
+                through.push(current.clone());
+                break Some((current.span(), UNREACHABLE_END));
+            }
+        };
+        through.push(current.clone());
+        if depth.is_some_and(|d| through.len() == d as usize) {
+            return Ok(vec);
+        }
+
+        if let Type::Ptr(_) | Type::Reference(_) = current {
+            break Some((next_ty.span(), UNREACHABLE_REF));
+        }
+        if is_unsized(next_ty) {
+            let sources = sized_sources(current, next_ty);
+            if sources.is_empty() {
+                break Some((next_ty.span(), UNREACHABLE_UNSIZED));
+            }
+            vec.extend(sources.into_iter().map(|(ty, alloc)| Level {
+                ty,
+                through: through.clone(),
+                alloc,
+            }));
+            // The sources are the last level, there is nothing past them either
+            through.push(next_ty.clone());
+            break Some((next_ty.span(), UNREACHABLE_END));
+        }
+        current = next_ty;
+    };
+
+    match (unreachable, depth) {
+        // The chain ended right at the requested level
+        (_, Some(d)) if through.len() == d as usize => Ok(vec),
+        (Some((span, msg)), Some(_)) => Err(Error::Special(span, msg)),
+        _ => Ok(vec),
+    }
+}
+
+/// The type arguments of the last segment of `path`, such as `K` and `V` of `HashMap<K, V>`
+pub(super) fn type_args(path: &syn::TypePath) -> Vec<&Type> {
+    match &path.path.segments.last().unwrap().arguments {
+        PathArguments::AngleBracketed(brac) => brac
+            .args
+            .iter()
+            .filter_map(|v| {
+                if let GenericArgument::Type(ty) = v {
+                    Some(ty)
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+const UNREACHABLE_REF: &str = concat!(
+    "`wrapDepth` cannot reach past a reference or pointer since it can't be created from an owned value\n",
+    "\tConsider lowering `wrapDepth` here"
+);
+
+const UNREACHABLE_END: &str = concat!(
+    "`wrapDepth` cannot reach past the end of the chain of inner types\n",
+    "\tConsider lowering `wrapDepth` here"
+);
+
+const UNREACHABLE_UNSIZED: &str = concat!(
+    "`wrapDepth` cannot reach an unsized type since `From` can only be derived for sized types\n",
+    "\tConsider lowering `wrapDepth` here"
+);
+
+fn is_unsized(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p.qself.is_none() && p.path.is_ident("str"),
+        Type::Slice(_) | Type::TraitObject(_) => true,
+        Type::Paren(p) => is_unsized(&p.elem),
+        Type::Group(g) => is_unsized(&g.elem),
+        _ => false,
+    }
+}
+
+/// Sized types that `outer` has `From` impls for in std, in place of its unsized `inner` type, and
+/// whether they are named through [`ALLOC`]
+fn sized_sources(outer: &Type, inner: &Type) -> Vec<(Type, bool)> {
+    let outer = match outer {
+        Type::Path(p) => p.path.segments.last().unwrap(),
+        _ => return vec![],
+    };
+    let lifetime = match &outer.arguments {
+        PathArguments::AngleBracketed(brac) => brac.args.iter().find_map(|arg| match arg {
+            GenericArgument::Lifetime(lt) => Some(lt),
+            _ => None,
+        }),
+        _ => None,
+    };
+
+    let alloc = Ident::new(ALLOC, Span::call_site());
+    match (outer.ident.to_string().as_str(), inner) {
+        ("Box" | "Rc" | "Arc" | "Cow", Type::Path(_)) => vec![
+            (parse_quote!(#alloc::string::String), true),
+            (parse_quote!(&#lifetime #inner), false),
+        ],
+        ("Box" | "Rc" | "Arc", Type::Slice(slice)) => {
+            let elem = &slice.elem;
+            vec![(parse_quote!(#alloc::vec::Vec<#elem>), true)]
+        }
+        ("Cow", Type::Slice(slice)) => {
+            let elem = &slice.elem;
+            vec![
+                (parse_quote!(#alloc::vec::Vec<#elem>), true),
+                (parse_quote!(&#lifetime #inner), false),
+            ]
+        }
+        _ => vec![],
+    }
+}
+
+/// Splits a `Box`, `Rc` or `Arc` of `dyn Trait` into the path of the pointer, to construct it
+/// with, and the bounds of the trait object
+pub(super) fn trait_object(
+    ty: &Type,
+) -> Option<(syn::Path, Punctuated<TypeParamBound, Token![+]>)> {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() => &p.path,
+        Type::Paren(p) => return trait_object(&p.elem),
+        Type::Group(g) => return trait_object(&g.elem),
+        _ => return None,
+    };
+
+    let last = path.segments.last()?;
+    if !["Box", "Rc", "Arc"].iter().any(|ptr| last.ident == ptr) {
+        return None;
+    }
+    let bounds = match &last.arguments {
+        PathArguments::AngleBracketed(brac) => match brac.args.first()? {
+            GenericArgument::Type(Type::TraitObject(obj)) => obj.bounds.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let mut ptr = path.clone();
+    ptr.segments.last_mut().unwrap().arguments = PathArguments::None;
+    Some((ptr, bounds))
+}
+
+/// Cuts `levels` off before the first level that is the derived type itself, since `From<Self>`
+/// conflicts with `impl<T> From<T> for T` in core. It is an error if `wrapDepth` explicitly asked
+/// for that level.
+pub(super) fn trim_self(
+    mut levels: Vec<Level>,
+    depth: Option<u32>,
+    ident: &Ident,
+    generics: &Generics,
+) -> Result<Vec<Level>, Error> {
+    if let Some(i) = levels
+        .iter()
+        .position(|level| is_self(&level.ty, ident, generics))
+    {
+        if depth.is_some() {
+            return Err(Error::Special(
+                levels[i].ty.span(),
+                concat!(
+                    "`wrapDepth` reaches the type itself and `From<Self>` conflicts with `impl<T> From<T> for T` in core\n",
+                    "\tConsider lowering `wrapDepth` here"
+                ),
+            ));
+        }
+        levels.truncate(i);
+    }
+    Ok(levels)
+}
+
+/// Whether `ty` is `Self` or the derived type by name with its own generics
+fn is_self(ty: &Type, ident: &Ident, generics: &Generics) -> bool {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() && p.path.segments.len() == 1 => &p.path.segments[0],
+        Type::Paren(p) => return is_self(&p.elem, ident, generics),
+        Type::Group(g) => return is_self(&g.elem, ident, generics),
+        _ => return false,
+    };
+
+    if path.ident == "Self" {
+        return true;
+    }
+    if &path.ident != ident {
+        return false;
+    }
+
+    // Lifetimes do not matter to coherence so only type and const arguments are compared
+    let params: Vec<_> = generics
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            syn::GenericParam::Type(t) => Some(&t.ident),
+            syn::GenericParam::Const(c) => Some(&c.ident),
+            syn::GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    let args: Vec<_> = match &path.arguments {
+        PathArguments::None => vec![],
+        PathArguments::AngleBracketed(brac) => brac
+            .args
+            .iter()
+            .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_)))
+            .collect(),
+        PathArguments::Parenthesized(_) => return false,
+    };
+
+    params.len() == args.len()
+        && params.iter().zip(args).all(|(param, arg)| match arg {
+            GenericArgument::Type(Type::Path(p)) => p.qself.is_none() && p.path.is_ident(*param),
+            GenericArgument::Const(syn::Expr::Path(p)) => {
+                p.qself.is_none() && p.path.is_ident(*param)
+            }
+            _ => false,
+        })
+}
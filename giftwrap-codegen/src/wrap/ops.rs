@@ -0,0 +1,185 @@
+use {
+    crate::{attrib::Op, field_member, fresh_param},
+    proc_macro2::TokenStream,
+    quote::{format_ident, quote},
+    syn::{self, parse_quote},
+};
+
+/// A single-field struct to forward operators to the field of
+struct Newtype<'a> {
+    ident: &'a syn::Ident,
+    generics: &'a syn::Generics,
+    field: &'a syn::Field,
+    /// Whether the inner type is bounded on the operators, unless replaced through `bound`
+    infer: bool,
+}
+
+/// Derives every operator in `ops` for the single-field struct `ident` by forwarding to its field,
+/// each bounded on the inner type implementing the same operator if `infer` is set
+pub(super) fn derive_ops(
+    ops: &[Op],
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field: &syn::Field,
+    infer: bool,
+) -> TokenStream {
+    let newtype = Newtype {
+        ident,
+        generics,
+        field,
+        infer,
+    };
+    ops.iter()
+        .map(|op| match op {
+            Op::Neg => newtype.unary("Neg"),
+            Op::Not => newtype.unary("Not"),
+            Op::Sum => newtype.fold("Sum"),
+            Op::Product => newtype.fold("Product"),
+            &op => {
+                let (name, scalar) = binary(op);
+                newtype.binary(name, scalar)
+            }
+        })
+        .collect()
+}
+
+/// The trait name of a binary operator, and whether it takes the inner type as right-hand side
+fn binary(op: Op) -> (&'static str, bool) {
+    match op {
+        Op::Add => ("Add", false),
+        Op::AddScalar => ("Add", true),
+        Op::Sub => ("Sub", false),
+        Op::SubScalar => ("Sub", true),
+        Op::Mul => ("Mul", false),
+        Op::MulScalar => ("Mul", true),
+        Op::Div => ("Div", false),
+        Op::DivScalar => ("Div", true),
+        Op::Rem => ("Rem", false),
+        Op::RemScalar => ("Rem", true),
+        Op::BitAnd => ("BitAnd", false),
+        Op::BitAndScalar => ("BitAnd", true),
+        Op::BitOr => ("BitOr", false),
+        Op::BitOrScalar => ("BitOr", true),
+        Op::BitXor => ("BitXor", false),
+        Op::BitXorScalar => ("BitXor", true),
+        Op::Shl => ("Shl", false),
+        Op::ShlScalar => ("Shl", true),
+        Op::Shr => ("Shr", false),
+        Op::ShrScalar => ("Shr", true),
+        Op::Neg | Op::Not | Op::Sum | Op::Product => unreachable!(),
+    }
+}
+
+impl Newtype<'_> {
+    /// Builds `Self` around `inner`
+    fn wrap(&self, inner: TokenStream) -> TokenStream {
+        match &self.field.ident {
+            Some(field) => quote!(Self { #field: #inner }),
+            None => quote!(Self(#inner)),
+        }
+    }
+
+    /// The generics of the struct with `predicate` added to its where clause if bounds are inferred
+    fn bounded(&self, predicate: syn::WherePredicate) -> syn::Generics {
+        crate::bounded(self.generics, self.infer.then_some(predicate))
+    }
+
+    /// `name` and `nameAssign` with either `Self` or the inner type as right-hand side
+    fn binary(&self, name: &str, scalar: bool) -> TokenStream {
+        let Self { ident, field, .. } = self;
+        let ty = &field.ty;
+        let member = field_member(self.field);
+        let op_trait = format_ident!("{}", name);
+        let method = format_ident!("{}", name.to_lowercase());
+        let assign_trait = format_ident!("{}Assign", name);
+        let assign_method = format_ident!("{}_assign", name.to_lowercase());
+        let (rhs, rhs_inner) = match scalar {
+            true => (quote!(#ty), quote!(__rhs)),
+            false => (quote!(Self), quote!(__rhs.#member)),
+        };
+        let result = self.wrap(quote!(::core::ops::#op_trait::#method(self.#member, #rhs_inner)));
+
+        let op_generics = self.bounded(parse_quote!(#ty: ::core::ops::#op_trait<Output = #ty>));
+        let (impl_gen, ty_gen, where_clause) = op_generics.split_for_impl();
+        let assign_generics = self.bounded(parse_quote!(#ty: ::core::ops::#assign_trait));
+        let (assign_impl_gen, _, assign_where_clause) = assign_generics.split_for_impl();
+        quote! {
+            #[automatically_derived]
+            impl #impl_gen ::core::ops::#op_trait<#rhs> for #ident #ty_gen #where_clause {
+                type Output = Self;
+
+                #[inline]
+                fn #method(self, __rhs: #rhs) -> Self {
+                    #result
+                }
+            }
+
+            #[automatically_derived]
+            impl #assign_impl_gen ::core::ops::#assign_trait<#rhs> for #ident #ty_gen #assign_where_clause {
+                #[inline]
+                fn #assign_method(&mut self, __rhs: #rhs) {
+                    ::core::ops::#assign_trait::#assign_method(&mut self.#member, #rhs_inner)
+                }
+            }
+        }
+    }
+
+    /// `Neg` or `Not`
+    fn unary(&self, name: &str) -> TokenStream {
+        let Self { ident, field, .. } = self;
+        let ty = &field.ty;
+        let member = field_member(self.field);
+        let op_trait = format_ident!("{}", name);
+        let method = format_ident!("{}", name.to_lowercase());
+        let result = self.wrap(quote!(::core::ops::#op_trait::#method(self.#member)));
+
+        let op_generics = self.bounded(parse_quote!(#ty: ::core::ops::#op_trait<Output = #ty>));
+        let (impl_gen, ty_gen, where_clause) = op_generics.split_for_impl();
+        quote! {
+            #[automatically_derived]
+            impl #impl_gen ::core::ops::#op_trait for #ident #ty_gen #where_clause {
+                type Output = Self;
+
+                #[inline]
+                fn #method(self) -> Self {
+                    #result
+                }
+            }
+        }
+    }
+
+    /// `Sum` or `Product`
+    fn fold(&self, name: &str) -> TokenStream {
+        let Self {
+            ident,
+            generics,
+            field,
+            ..
+        } = self;
+        let ty = &field.ty;
+        let member = field_member(self.field);
+        let fold_trait = format_ident!("{}", name);
+        let method = format_ident!("{}", name.to_lowercase());
+        let iter = fresh_param(generics, "__I");
+        let result = self.wrap(quote! {
+            <#ty as ::core::iter::#fold_trait>::#method(
+                ::core::iter::Iterator::map(__iter, |__item| __item.#member),
+            )
+        });
+
+        let fold_generics = self.bounded(parse_quote!(#ty: ::core::iter::#fold_trait));
+        let (impl_gen, ty_gen, where_clause) = fold_generics.split_for_impl();
+        quote! {
+            #[automatically_derived]
+            impl #impl_gen ::core::iter::#fold_trait for #ident #ty_gen #where_clause {
+                #[inline]
+                fn #method<#iter>(__iter: #iter) -> Self
+                where
+                    #iter: ::core::iter::Iterator<Item = Self>,
+                {
+                    #result
+                }
+            }
+        }
+    }
+}
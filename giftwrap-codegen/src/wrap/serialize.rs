@@ -0,0 +1,180 @@
+use {
+    crate::{bounded, field_member, fresh_lifetime, fresh_param},
+    proc_macro2::{TokenStream, TokenTree},
+    quote::{format_ident, quote},
+    syn::{self, parse_quote, Type},
+};
+
+/// Derives `Serialize` and `Deserialize` for a single-field struct as its inner value, bounded on
+/// the inner type implementing them if `infer` is set
+pub(super) fn derive_serde_struct(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field: &syn::Field,
+    infer: bool,
+) -> TokenStream {
+    let ty = &field.ty;
+    let member = field_member(field);
+    let from_inner = match &field.ident {
+        Some(ident) => quote!(Self { #ident: __inner }),
+        None => quote!(Self(__inner)),
+    };
+    let bound = infer && !mentions(ty, ident);
+    let ser_generics = bounded(
+        generics,
+        bound.then(|| parse_quote!(#ty: ::serde::Serialize)),
+    );
+    let (impl_gen, ty_gen, where_clause) = ser_generics.split_for_impl();
+    let ser = fresh_param(generics, "__S");
+    let serialize = quote! {
+        #[automatically_derived]
+        impl #impl_gen ::serde::Serialize for #ident #ty_gen #where_clause {
+            #[inline]
+            fn serialize<#ser>(&self, __serializer: #ser) -> ::core::result::Result<#ser::Ok, #ser::Error>
+            where
+                #ser: ::serde::Serializer,
+            {
+                ::serde::Serialize::serialize(&self.#member, __serializer)
+            }
+        }
+    };
+
+    let de = fresh_lifetime(generics, "de");
+    let mut de_generics = bounded(
+        generics,
+        bound.then(|| parse_quote!(#ty: ::serde::Deserialize<#de>)),
+    );
+    de_generics.params.insert(0, parse_quote!(#de));
+    let (impl_gen, _, where_clause) = de_generics.split_for_impl();
+    let deser = fresh_param(generics, "__D");
+    quote! {
+        #serialize
+
+        #[automatically_derived]
+        impl #impl_gen ::serde::Deserialize<#de> for #ident #ty_gen #where_clause {
+            #[inline]
+            fn deserialize<#deser>(__deserializer: #deser) -> ::core::result::Result<Self, #deser::Error>
+            where
+                #deser: ::serde::Deserializer<#de>,
+            {
+                let __inner = <#ty as ::serde::Deserialize<#de>>::deserialize(__deserializer)?;
+                ::core::result::Result::Ok(#from_inner)
+            }
+        }
+    }
+}
+
+/// Derives `Serialize` for an enum as the bare inner value of every variant, and `Deserialize` by
+/// trying the inner type of every variant in `wrapped` in declaration order. The inner type of a
+/// variant is bounded on implementing them unless the variant has a `bound` in its place
+pub(super) fn derive_serde_enum(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    variants: &[(&syn::Variant, &syn::Field, Option<Vec<syn::WherePredicate>>)],
+    wrapped: &[(&syn::Variant, &syn::Field, Option<Vec<syn::WherePredicate>>)],
+) -> TokenStream {
+    let bind = |var: &syn::Variant, field: &syn::Field| {
+        let varname = &var.ident;
+        match &field.ident {
+            Some(field) => quote!(Self::#varname { #field: __inner }),
+            None => quote!(Self::#varname(__inner)),
+        }
+    };
+
+    let bounds = |variants: &[(&syn::Variant, &syn::Field, Option<Vec<syn::WherePredicate>>)],
+                  inferred: &dyn Fn(&Type) -> syn::WherePredicate| {
+        variants
+            .iter()
+            .flat_map(|(_, field, bound)| match bound {
+                Some(bound) => bound.clone(),
+                None if mentions(&field.ty, ident) => vec![],
+                None => vec![inferred(&field.ty)],
+            })
+            .collect::<Vec<syn::WherePredicate>>()
+    };
+
+    let ser_generics = bounded(
+        generics,
+        bounds(variants, &|ty| parse_quote!(#ty: ::serde::Serialize)),
+    );
+    let (impl_gen, ty_gen, where_clause) = ser_generics.split_for_impl();
+    let ser = fresh_param(generics, "__S");
+    let ser_arms = variants.iter().map(|(var, field, _)| bind(var, field));
+    let mut stream = quote! {
+        #[automatically_derived]
+        impl #impl_gen ::serde::Serialize for #ident #ty_gen #where_clause {
+            fn serialize<#ser>(&self, __serializer: #ser) -> ::core::result::Result<#ser::Ok, #ser::Error>
+            where
+                #ser: ::serde::Serializer,
+            {
+                match self {
+                    #(#ser_arms => ::serde::Serialize::serialize(__inner, __serializer),)*
+                }
+            }
+        }
+    };
+    if wrapped.is_empty() {
+        return stream;
+    }
+
+    // The untagged helper enum has the same generics, the phantom variant uses every one of them
+    let de = fresh_lifetime(generics, "de");
+    let de_bounds = bounds(wrapped, &|ty| parse_quote!(#ty: ::serde::Deserialize<#de>));
+    let bound = quote!(#(#de_bounds),*).to_string();
+    let helper = format_ident!("__Giftwrap{}", ident);
+    let helper_variants = wrapped.iter().map(|(var, ..)| &var.ident);
+    let helper_types: Vec<&Type> = wrapped.iter().map(|(_, field, _)| &field.ty).collect();
+    let de_arms = wrapped.iter().map(|(var, field, _)| {
+        let varname = &var.ident;
+        let self_var = bind(var, field);
+        quote!(#helper::#varname(__inner) => #self_var)
+    });
+    let name = ident.to_string();
+    let (_, _, plain_where_clause) = generics.split_for_impl();
+    let helper_params = &generics.params;
+
+    let mut de_generics = bounded(generics, de_bounds.iter().cloned());
+    de_generics.params.insert(0, parse_quote!(#de));
+    let (impl_gen, _, where_clause) = de_generics.split_for_impl();
+    let deser = fresh_param(generics, "__D");
+    stream.extend(quote! {
+        const _: () = {
+            #[derive(::serde::Deserialize)]
+            #[serde(untagged, rename = #name, bound(deserialize = #bound))]
+            enum #helper<#helper_params> #plain_where_clause {
+                #(#helper_variants(#helper_types),)*
+                #[serde(skip)]
+                __GiftwrapPhantom(::core::marker::PhantomData<fn() -> #ident #ty_gen>),
+            }
+
+            #[automatically_derived]
+            impl #impl_gen ::serde::Deserialize<#de> for #ident #ty_gen #where_clause {
+                fn deserialize<#deser>(__deserializer: #deser) -> ::core::result::Result<Self, #deser::Error>
+                where
+                    #deser: ::serde::Deserializer<#de>,
+                {
+                    ::core::result::Result::Ok(
+                        match <#helper #ty_gen as ::serde::Deserialize<#de>>::deserialize(__deserializer)? {
+                            #(#de_arms,)*
+                            #helper::__GiftwrapPhantom(_) => ::core::unreachable!(),
+                        },
+                    )
+                }
+            }
+        };
+    });
+    stream
+}
+
+/// Whether `ty` mentions the type being derived for. Bounding on such a type would make the impl
+/// require itself, so it is left out and holds through the other bounds instead
+fn mentions(ty: &Type, ident: &syn::Ident) -> bool {
+    fn walk(tokens: TokenStream, ident: &syn::Ident) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(i) => i == *ident || i == "Self",
+            TokenTree::Group(g) => walk(g.stream(), ident),
+            _ => false,
+        })
+    }
+    walk(quote!(#ty), ident)
+}
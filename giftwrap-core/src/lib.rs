@@ -0,0 +1,83 @@
+//! Traits and types for the `giftwrap` derives, which as a `proc-macro` crate can't export any.
+//!
+//! Add `#[giftwrap(traits)]` to a type deriving `Wrap` or `Unwrap` to have these implemented, so
+//! that generic code can be written against every newtype and enum at once.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::fmt;
+
+/// A newtype around a single inner value, implemented by `Wrap` for structs
+pub trait Wrapper: Sized {
+    /// The type of the wrapped field
+    type Inner;
+
+    /// Wraps `inner` in `Self`
+    fn from_inner(inner: Self::Inner) -> Self;
+
+    /// Unwraps `self` into its inner value
+    fn into_inner(self) -> Self::Inner;
+}
+
+/// The inner type of one or more variants of the enum `E`, implemented by `Unwrap` for every type
+/// `TryFrom<E>` is derived for
+pub trait VariantOf<E>: Sized {
+    /// Unwraps `e` if it is a variant holding `Self`, otherwise hands it back in the error
+    fn try_from_enum(e: E) -> Result<Self, UnwrapError<E>>;
+
+    /// Borrows the inner value of `e` if it is a variant holding `Self`
+    fn ref_from_enum(e: &E) -> Option<&Self>;
+}
+
+/// The error of unwrapping a variant that does not hold the requested type, which hands back the
+/// value so it isn't lost
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnwrapError<T> {
+    value: T,
+    variant: &'static str,
+    into: &'static str,
+}
+
+impl<T> UnwrapError<T> {
+    /// An error for `value`, which is the variant `variant` (such as `MyEnum::Text`) and could not
+    /// be converted into the type named `into`
+    pub const fn new(value: T, variant: &'static str, into: &'static str) -> Self {
+        Self {
+            value,
+            variant,
+            into,
+        }
+    }
+
+    /// The path of the variant that was unwrapped
+    pub fn variant(&self) -> &'static str {
+        self.variant
+    }
+
+    /// The name of the type it could not be unwrapped into
+    pub fn into_type(&self) -> &'static str {
+        self.into
+    }
+
+    /// Takes back the value that could not be unwrapped
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T> fmt::Debug for UnwrapError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnwrapError")
+            .field("variant", &self.variant)
+            .field("into", &self.into)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for UnwrapError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Can't convert {} into {}", self.variant, self.into)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for UnwrapError<T> {}
@@ -1,24 +1,113 @@
+//! Derive macros to wrap and unwrap your types the stylish way.
+//!
+//! As a `proc-macro` crate `giftwrap` can't export any traits or types, those live in the
+//! companion [`giftwrap-core`](https://docs.rs/giftwrap-core) crate. Add it as a dependency and
+//! `#[giftwrap(traits)]` to a type to have its `Wrapper`, `VariantOf` and `UnwrapError` used.
+//! Where `giftwrap-core` is re-exported by another crate, point the derives to it through
+//! `#[giftwrap(crate = other::giftwrap_core)]`.
+//!
+//! The generated code only refers to `::core`, so both derives work in `#![no_std]` crates. The
+//! `String` and `Vec` sources of `Box<str>`, `Arc<[T]>` and the like are named through `alloc`,
+//! which the impls bring into scope themselves.
+//!
+//! Every path in the generated code is fully qualified and every binding is prefixed with `__`, as
+//! are the type parameters of generated trait impls, so items such as a local `Ok`, `const f` or
+//! `enum E` don't change the expansion. Generated trait impls are marked `#[automatically_derived]`
+//! to be left out of lints and coverage.
+//!
+//! Generated impls have the where clause of the type. `#[giftwrap(bound = "T: Debug + 'static")]`
+//! adds predicates to it, on the type for every impl or on a variant for the impls generated for
+//! that variant. These replace the bounds the derives infer for the inner type, such as
+//! `Inner: PartialEq` for `eq`, so `bound = ""` drops those alone. Bounds on parameters introduced
+//! by an impl itself, such as the index of `Index`, are always kept.
+//!
+//! Options may be split over several `#[giftwrap(...)]` attributes on the same item. Setting an
+//! option twice, or one that isn't known on that item, is an error.
+//!
+//! The impls are generated by the [`giftwrap-codegen`](https://docs.rs/giftwrap-codegen) crate,
+//! which build scripts and other macros can use directly on a type definition.
 extern crate proc_macro;
-use harled::{Error, Kind};
 use proc_macro::TokenStream;
 
 //TODO noWrap/wrapDepth -> giftwrap(noWrap/wrapDepth) in docs
 
-#[macro_use]
-mod wrap;
-#[macro_use]
-mod unwrap;
-
-pub(crate) mod attrib;
-
 /// Derve macro for `From<T>` where `T` is the inner type(s) of your struct or enum.
 ///
 /// Using `#[giftwrap(wrapDepth = n)]` `From` is derived for every type in the chain, which is useful for
 /// types such as `Box<T>` and `Arc<Mutex<T>>`. Setting wrapDepth to 0 will derive for all inner
 /// types. Default depth is 1.
 ///
+/// A chain that reaches the type itself, such as `Box<Expr>` in a recursive `Expr`, stops right
+/// before it when deriving for all inner types since `From<Expr> for Expr` already exists in core.
+/// Explicitly asking for that level through `wrapDepth` is an error.
+///
+/// The chain also ends at references and pointers, which can't be created from an owned value, and
+/// before unsized types such as `str`, `[T]` and `dyn Trait`. Where std has a sized source for the
+/// pointer those are derived instead, e.g. `String` and `&str` for `Arc<str>` or `Vec<T>` for
+/// `Box<[T]>`. A `wrapDepth` past the end of the chain, such as `wrapDepth = 2` on `i32`, is an
+/// error.
+///
 /// Any enum variant annotated with `#[giftwrap(noWrap = true)]` will be ignored.
 ///
+/// Two variants with the same inner type are an error. Types are compared after normalizing paths
+/// through `std`, `core` and `alloc` or the prelude, so `String` and `::std::string::String` are the
+/// same, as are `&'_ str` and `&str`. An inner type named through an alias can be declared the same
+/// as the aliased type with `#[giftwrap(sameAs = String)]` on its variant.
+///
+/// A variant holding a `Box`, `Rc` or `Arc` of `dyn Trait` can be annotated with
+/// `#[giftwrap(anyImpl)]` to derive `impl<E: Trait + 'static> From<E>` with the bounds of the trait
+/// object, instead of `From` for the pointer itself. The enum itself must not implement the trait.
+///
+/// Every other variant of such an enum must use `noWrap`, whatever its inner type. This is stricter
+/// than coherence, which accepts `From` for a local type that doesn't implement the trait, but a
+/// derive can't tell a local type from an imported one nor see which traits it implements. `From`
+/// for the other variants can still be written by hand where the compiler accepts it.
+///
+/// For fieldless enums `TryFrom` is derived for the integer type of the discriminant, as set
+/// through `#[repr]` or `isize` by default. The error is a generated `<Enum>DiscriminantError`
+/// holding the invalid value. Both generated errors implement `core::error::Error`, which requires
+/// Rust 1.81.
+///
+/// With `#[giftwrap(strings)]` on a fieldless enum `TryFrom<&str>` and `FromStr` are derived by
+/// variant name as well. Names are the variant identifiers, converted by
+/// `rename_all = "kebab-case"` (or any other of serde's `rename_all` rules) if set, and can be
+/// overridden per variant with `#[giftwrap(name = "...")]`. Additional names are accepted through
+/// `#[giftwrap(alias = ["..."])]`. The error is a generated `<Enum>NameError` listing the names.
+///
+/// With `#[giftwrap(traits)]` on a struct `giftwrap_core::Wrapper` is implemented as well.
+///
+/// With `#[giftwrap(constNew)]` on a struct `const fn new(inner) -> Self` is derived as well, for
+/// use in `const` items where `From::from` can't be called. It can be renamed through
+/// `constNew = "with_port"`, and its visibility set through `constVis = "pub(crate)"`.
+///
+/// With `#[giftwrap(ops = [add, mul_scalar, neg, sum])]` on a struct the listed operators are
+/// derived by forwarding to the field, each bounded on the inner type implementing the same
+/// operator. Every binary operator of `std::ops` (`add`, `sub`, `mul`, `div`, `rem`, `bitand`,
+/// `bitor`, `bitxor`, `shl` and `shr`) is derived along with its `*Assign` form, with the struct
+/// itself as right-hand side or, with the `_scalar` suffix, the inner type. `neg` and `not` derive
+/// the unary operators and `sum` and `product` derive the `std::iter` traits.
+///
+/// With `#[giftwrap(collection)]` on a struct holding a collection, such as `Tags(Vec<Tag>)` or
+/// `Registry(HashMap<Id, Entry>)`, `FromIterator` and `Extend` of its elements, `IntoIterator` for
+/// owned, `&` and `&mut` receivers, and `Index`/`IndexMut` are derived by delegating to the field.
+/// The element type is the first type argument of the field type, or the first two as a tuple for
+/// types named `*Map`.
+///
+/// With `#[giftwrap(refCast)]` on a `#[repr(transparent)]` struct `fn from_ref(&Inner) -> &Self`,
+/// `fn from_mut(&mut Inner) -> &mut Self` and the slice forms `from_slice` and `from_mut_slice` are
+/// derived, to view borrowed data as the newtype without copying. The casts are only sound for
+/// `repr(transparent)` so the derive refuses without it, and the layout is asserted at compile time.
+///
+/// With `#[giftwrap(serde)]` `serde::Serialize` and `serde::Deserialize` are derived as well,
+/// through the `serde` crate of the user. A struct is (de)serialized as its field. An enum is
+/// serialized as the inner value of its variant, which must all have a single field, and is
+/// deserialized by trying the inner type of every wrapped variant in declaration order, so a
+/// variant with `noWrap` or `anyImpl` can be serialized but is never deserialized into.
+///
+/// Before anything is derived every `From` impl is checked against every other, and against
+/// `impl<T> From<T> for T` in core, so that generic variants which would overlap are reported
+/// where they are declared rather than as conflicting implementations in the expansion.
+///
 /// # Example
 /// ```ignore
 /// use std::sync::{Arc, Mutex};
@@ -55,33 +144,80 @@ pub(crate) mod attrib;
 ///
 /// impl From<Mutex<bool>> for SomeEnum {
 ///     fn from(f: Mutex<bool>) -> Self {
-///         SomeEnum::DeepVariant(Arc::<_>::from(f))
+///         SomeEnum::DeepVariant(<Arc<Mutex<bool>>>::from(f))
 ///     }
 /// }
 ///
 /// impl From<bool> for SomeEnum {
 ///     fn from(f: bool) -> Self {
-///         SomeEnum::DeepVariant(Arc::<_>::from(Mutex::<_>::from(f)))
+///         SomeEnum::DeepVariant(<Arc<Mutex<bool>>>::from(<Mutex<bool>>::from(f)))
 ///     }
 /// }
 /// ```
 #[proc_macro_derive(Wrap, attributes(giftwrap))]
 pub fn derive_wrap(input: TokenStream) -> TokenStream {
-    let wrap: Result<wrap::Derive, _> = harled::parse(input);
-    match wrap.map_err(|e| match e {
-        Error::Unsupported(Kind::Union, span) => wrap::Error::For(span, "Union").into(),
-        Error::Syn(syn) => syn,
-        _ => unreachable!(),
-    }) {
-        Ok(wrap) => wrap.derive().into(),
-        Err(syn) => syn.to_compile_error().into(),
-    }
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    giftwrap_codegen::derive_wrap(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }
 
 /// Derve macro for `impl From<S> for T` and `impl TryFrom<E> for T` for structs (`S`) and enums (`E`) where `T` is the inner type(s).
 ///
 /// Any enum variant annotated with `#[giftwrap(noUnwrap = true)]` will be ignored.
 ///
+/// Variants with the same inner type share a single `TryFrom`, where types are compared the same way
+/// as for [`Wrap`](derive.Wrap.html) including `sameAs`.
+///
+/// For fieldless enums `From` is derived for the integer type of the discriminant instead, as set
+/// through `#[repr]` or `isize` by default. With `#[giftwrap(strings)]` `From` is also derived for
+/// `&'static str`, giving the name of the variant as described for [`Wrap`](derive.Wrap.html).
+///
+/// With `#[giftwrap(helpers)]` on a struct the inherent `fn into_inner(self) -> Inner`,
+/// `fn inner(&self) -> &Inner` and `fn inner_mut(&mut self) -> &mut Inner` are derived as well. If
+/// the field is a bare type parameter, as in `Wrapper<T>(T)`, so is
+/// `fn map<U>(self, f: impl FnOnce(T) -> U) -> Wrapper<U>`, and `From<Wrapper<T>> for T` is not
+/// derived since it would break the orphan rule. Any of them named the same as a `constGet` or
+/// `constIntoInner` accessor is left to that accessor.
+///
+/// With `#[giftwrap(eq)]` on a struct `PartialEq<Inner> for Struct` and `PartialEq<Struct> for Inner`
+/// are derived as well, and with `#[giftwrap(ord)]` the matching `PartialOrd` impls, which need
+/// `eq` or impls of their own. Neither can be used on a bare type parameter field since
+/// `PartialEq<T>` would overlap with `PartialEq<Self>`. On an enum `eq` derives `PartialEq<T>` for
+/// every type `TryFrom` is derived for, which is only equal to a variant holding an equal value.
+///
+/// With `#[giftwrap(constGet)]` on a struct `const fn get(&self) -> &Inner` is derived as well, and
+/// with `#[giftwrap(constIntoInner)]` `const fn into_inner(self) -> Inner`, which only compiles for
+/// inner types without a destructor, such as `Copy` types. Inner types from `std` that have one,
+/// such as `String` or `Vec<T>`, are rejected by the derive, any other fails to compile with E0493
+/// and needs `helpers` for a non-`const` `into_inner` instead. Like `constNew` for
+/// [`Wrap`](derive.Wrap.html) they can be renamed through e.g. `constGet = "port"`, and their
+/// visibility set through `constVis`.
+///
+/// With `#[giftwrap(traits)]` on an enum the error of `TryFrom` and `try_into_<variant>` is
+/// `giftwrap_core::UnwrapError<MyEnum>`, which hands back the value, and
+/// `giftwrap_core::VariantOf<MyEnum>` is implemented for every type `TryFrom` is derived for.
+/// Through it the enum gets `fn extract<T>(self) -> Result<T, Self>`, `fn get<T>(&self) ->
+/// Option<&T>` and `fn is<T>(&self) -> bool` for any of those types, without naming the variant.
+///
+/// With `#[giftwrap(kind = MyEnumKind)]` on an enum a fieldless `MyEnumKind` is generated with a
+/// unit variant for every variant, including those skipped with `noWrap` or `noUnwrap`, along with
+/// `fn kind(&self) -> MyEnumKind` and `From<&MyEnum> for MyEnumKind`. It derives `Debug`, `Clone`,
+/// `Copy`, `PartialEq`, `Eq` and `Hash` unless set otherwise through `kindDerive = [Debug, ...]`.
+///
+/// With `#[giftwrap(reflect)]` on an enum an associated `MyEnum::VARIANTS` table of generated
+/// `MyEnumVariantInfo` is derived, holding the name of every variant, its inner type as written,
+/// and whether `Wrap` and `Unwrap` cover it, along with `fn variant_name(&self) -> &'static str`.
+/// Since a derive cannot see which other derives are used, `wrap` tells whether the variant would
+/// be wrapped if `Wrap` is derived as well.
+///
+/// A field that is a bare type parameter, such as `struct Wrapper<T>(T)`, cannot be unwrapped
+/// through `From`/`TryFrom` since `impl<T> From<Wrapper<T>> for T` breaks the orphan rule. Annotate
+/// the field or variant with `#[giftwrap(intoInner = true)]` to instead derive an inherent
+/// `fn into_inner(self) -> T` for structs, or `fn try_into_<variant>(self) -> Result<T, &'static str>`
+/// for enum variants. On a struct `#[giftwrap(helpers)]` derives the same `into_inner` along with
+/// the other helpers.
+///
 /// # Example
 /// ```ignore
 /// use std::convert::TryFrom;
@@ -122,39 +258,8 @@ pub fn derive_wrap(input: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro_derive(Unwrap, attributes(giftwrap))]
 pub fn derive_unwrap(input: TokenStream) -> TokenStream {
-    let unwrap: Result<unwrap::Derive, _> = harled::parse(input);
-    match unwrap.map_err(|e| match e {
-        Error::Unsupported(Kind::Union, span) => unwrap::Error::For(span, "Union").into(),
-        Error::Syn(syn) => syn,
-        _ => unreachable!(),
-    }) {
-        Ok(unwrap) => unwrap.derive().into(),
-        Err(syn) => syn.to_compile_error().into(),
-    }
-}
-
-pub(crate) enum GetFieldError {
-    Unit(proc_macro2::Span),
-    NotSingle(proc_macro2::Span),
-}
-
-pub(crate) fn get_field(fields: &syn::Fields) -> Result<&syn::Field, GetFieldError> {
-    use syn::spanned::Spanned;
-    match fields {
-        syn::Fields::Named(f) => {
-            if f.named.len() != 1 {
-                Err(GetFieldError::NotSingle(f.brace_token.span))
-            } else {
-                Ok(f.named.first().unwrap())
-            }
-        }
-        syn::Fields::Unnamed(f) => {
-            if f.unnamed.len() != 1 {
-                Err(GetFieldError::NotSingle(f.paren_token.span))
-            } else {
-                Ok(f.unnamed.first().unwrap())
-            }
-        }
-        syn::Fields::Unit => Err(GetFieldError::Unit(fields.span())),
-    }
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    giftwrap_codegen::derive_unwrap(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }